@@ -1,8 +1,8 @@
-use std::time::Duration;
+use std::{path::PathBuf, time::Duration};
 
 use beacon_node::beacon_chain::slot_clock::{SlotClock, SystemTimeSlotClock};
 use environment::{Environment, EnvironmentBuilder};
-use eth2_network_config::{Eth2NetworkConfig, DEFAULT_HARDCODED_NETWORK};
+use eth2_network_config::{Eth2NetworkConfig, HARDCODED_NET_NAMES};
 use store::{BeaconState, ChainSpec, EnrForkId, EthSpec, Slot};
 
 #[derive(Clone)]
@@ -14,9 +14,7 @@ pub struct BeaconContext<E: EthSpec> {
 }
 
 impl<E: EthSpec> BeaconContext<E> {
-    pub fn build(spec: ChainSpec) -> Result<Self, String> {
-        let eth2_network_config = Eth2NetworkConfig::constant(DEFAULT_HARDCODED_NETWORK)?
-            .ok_or("Failed to build Eth2 network config")?;
+    pub fn build(eth2_network_config: Eth2NetworkConfig, spec: ChainSpec) -> Result<Self, String> {
         let genesis_state = eth2_network_config.beacon_state()?;
         let slot_clock = SystemTimeSlotClock::new(
             spec.genesis_slot,
@@ -34,10 +32,11 @@ impl<E: EthSpec> BeaconContext<E> {
         Ok(beacon_context)
     }
 
-    pub fn new(genesis_state: BeaconState<E>, spec: ChainSpec) -> Result<Self, String> {
-        let eth2_network_config = Eth2NetworkConfig::constant(DEFAULT_HARDCODED_NETWORK)?
-            .ok_or("Failed to build Eth2 network config")?;
-
+    pub fn new(
+        genesis_state: BeaconState<E>,
+        eth2_network_config: Eth2NetworkConfig,
+        spec: ChainSpec,
+    ) -> Result<Self, String> {
         let slot_clock = SystemTimeSlotClock::new(
             spec.genesis_slot,
             Duration::from_secs(genesis_state.genesis_time()),
@@ -66,16 +65,33 @@ impl<E: EthSpec> BeaconContext<E> {
     }
 }
 
+/// Loads the network config of either a hardcoded network (mainnet, goerli, sepolia...)
+/// or a custom testnet directory.
+pub fn load_eth2_network_config(network: &str) -> Result<Eth2NetworkConfig, String> {
+    if HARDCODED_NET_NAMES.contains(&network) {
+        Eth2NetworkConfig::constant(network)?
+            .ok_or(format!("Failed to build Eth2 network config for '{network}'"))
+    } else {
+        let testnet_dir = PathBuf::from(network);
+
+        if testnet_dir.is_dir() {
+            Eth2NetworkConfig::load(testnet_dir)
+        } else {
+            Err(format!(
+                "'{network}' is neither a known network ({}) nor a testnet directory",
+                HARDCODED_NET_NAMES.join(", ")
+            ))
+        }
+    }
+}
+
 pub fn build_environment<E: EthSpec>(
     environment_builder: EnvironmentBuilder<E>,
-) -> Result<(Environment<E>, Eth2NetworkConfig), String> {
-    let eth2_network_config = Eth2NetworkConfig::constant(DEFAULT_HARDCODED_NETWORK)?
-        .ok_or("Failed to build Eth2 network config")?;
-    let environment = environment_builder
-        .eth2_network_config(eth2_network_config.clone())?
+    eth2_network_config: Eth2NetworkConfig,
+) -> Result<Environment<E>, String> {
+    environment_builder
+        .eth2_network_config(eth2_network_config)?
         .null_logger()?
         .multi_threaded_tokio_runtime()?
-        .build()?;
-
-    Ok((environment, eth2_network_config))
+        .build()
 }
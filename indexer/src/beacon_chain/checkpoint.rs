@@ -0,0 +1,86 @@
+use std::{fs, sync::Arc};
+
+use eth2::types::{BlockId, StateId};
+use store::{BeaconState, ChainSpec, EthSpec, SignedBeaconBlock, Slot};
+
+use crate::beacon_node_client::BeaconNodeClient;
+
+/// A finalized state and its block, used to start indexing from somewhere else than genesis.
+#[derive(Clone)]
+pub struct Checkpoint<E: EthSpec> {
+    pub state: BeaconState<E>,
+    pub block: Arc<SignedBeaconBlock<E>>,
+}
+
+impl<E: EthSpec> Checkpoint<E> {
+    pub fn load(state_path: &str, block_path: &str, spec: &ChainSpec) -> Result<Self, String> {
+        let state_bytes =
+            fs::read(state_path).map_err(|err| format!("Can't read '{state_path}': {err}"))?;
+        let block_bytes =
+            fs::read(block_path).map_err(|err| format!("Can't read '{block_path}': {err}"))?;
+
+        let state = BeaconState::from_ssz_bytes(&state_bytes, spec)
+            .map_err(|err| format!("Can't decode the checkpoint state: {err:?}"))?;
+        let block = SignedBeaconBlock::from_ssz_bytes(&block_bytes, spec)
+            .map_err(|err| format!("Can't decode the checkpoint block: {err:?}"))?;
+
+        Self::new(state, block, spec)
+    }
+
+    /// Downloads the latest finalized block of a beacon node and the state it produced
+    pub async fn fetch(client: &BeaconNodeClient, spec: &ChainSpec) -> Result<Self, String> {
+        let block = client
+            .get_block::<E>(BlockId::Finalized)
+            .await
+            .map_err(|err| format!("Can't get the finalized block: {err:?}"))?
+            .ok_or("The beacon node has no finalized block")?
+            .data;
+
+        let state = client
+            .get_state::<E>(StateId::Root(block.state_root()))
+            .await
+            .map_err(|err| format!("Can't get the finalized state: {err:?}"))?
+            .ok_or("The beacon node doesn't have the finalized state")?;
+
+        Self::new(state, block, spec)
+    }
+
+    fn new(
+        mut state: BeaconState<E>,
+        block: SignedBeaconBlock<E>,
+        spec: &ChainSpec,
+    ) -> Result<Self, String> {
+        if block.slot() != state.slot() {
+            return Err(format!(
+                "The checkpoint state must be at the same slot as its block ({} != {})",
+                state.slot(),
+                block.slot()
+            ));
+        }
+
+        let state_root = state
+            .update_tree_hash_cache()
+            .map_err(|err| format!("Can't compute the checkpoint state root: {err:?}"))?;
+
+        if block.state_root() != state_root {
+            return Err(format!(
+                "The checkpoint block state root doesn't match the checkpoint state ({:?} != {:?})",
+                block.state_root(),
+                state_root
+            ));
+        }
+
+        state
+            .build_all_caches(spec)
+            .map_err(|err| format!("Can't build the checkpoint state caches: {err:?}"))?;
+
+        Ok(Checkpoint {
+            state,
+            block: Arc::new(block),
+        })
+    }
+
+    pub fn slot(&self) -> Slot {
+        self.state.slot()
+    }
+}
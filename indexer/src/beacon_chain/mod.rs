@@ -1 +1,2 @@
 pub mod beacon_context;
+pub mod checkpoint;
@@ -10,7 +10,7 @@ use eth2::{
     BeaconNodeHttpClient, Timeouts,
 };
 use futures::Future;
-use lighthouse_types::{Epoch, EthSpec, SignedBeaconBlock};
+use lighthouse_types::{BeaconState, Epoch, EthSpec, SignedBeaconBlock};
 use sensitive_url::SensitiveUrl;
 
 use crate::errors::IndexerError;
@@ -42,6 +42,19 @@ impl BeaconNodeClient {
             .map_err(|inner_error| IndexerError::NodeError { inner_error })
     }
 
+    pub async fn get_state<E: EthSpec>(
+        &self,
+        state: StateId,
+    ) -> Result<Option<BeaconState<E>>, IndexerError> {
+        let client = self.client.clone();
+
+        client
+            .get_debug_beacon_states::<E>(state)
+            .await
+            .map(|response| response.map(|response| response.data))
+            .map_err(|inner_error| IndexerError::NodeError { inner_error })
+    }
+
     pub async fn get_block_root(
         &self,
         block: BlockId,
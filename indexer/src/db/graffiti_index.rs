@@ -0,0 +1,31 @@
+use types::{
+    graffiti::{normalize, shard_id, GraffitiShardModel, GraffitiShardModelWithId},
+    path::FromPath,
+    persistable::ResolvablePersistable,
+};
+
+/// Adds the slot of a canonical block to the slots of its graffiti,
+/// or removes it if the block has been orphaned
+pub fn update(base_dir: &str, graffiti: &str, slot: u64, is_canonical: bool) -> Result<(), String> {
+    let graffiti = normalize(graffiti);
+
+    if graffiti.is_empty() {
+        return Ok(());
+    }
+
+    let id = shard_id(&graffiti);
+    let mut shard = GraffitiShardModel::from_path(base_dir, &id).unwrap_or_default();
+    let slots = shard.slots.entry(graffiti.clone()).or_default();
+
+    if is_canonical {
+        slots.insert(slot);
+    } else {
+        slots.remove(&slot);
+    }
+
+    if slots.is_empty() {
+        shard.slots.remove(&graffiti);
+    }
+
+    GraffitiShardModelWithId { id, model: shard }.save(base_dir)
+}
@@ -1,8 +1,22 @@
-use std::ops::Range;
+use std::{
+    collections::VecDeque,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    mem,
+    ops::Range,
+    sync::Arc,
+};
 
 use eth1::{DepositCache, DepositLog, SszDepositCache};
-use lighthouse_types::{BeaconState, ChainSpec, Deposit, EthSpec, RelativeEpoch, Slot};
-use serde::{de::Visitor, ser::SerializeSeq, Deserialize, Deserializer, Serialize, Serializer};
+use lighthouse_types::{
+    BeaconState, ChainSpec, Deposit, Epoch, EthSpec, Hash256, RelativeEpoch, SignedBeaconBlock,
+    Slot, SyncCommittee,
+};
+use serde::{
+    de::{SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use shared::utils::clock::Clock;
 use ssz::{Decode, Encode};
 use state_processing::{
     per_block_processing::{
@@ -11,17 +25,48 @@ use state_processing::{
     per_epoch_processing, per_slot_processing, BlockSignatureStrategy, ConsensusContext,
     VerifyBlockRoot,
 };
+use tracing::warn;
 use types::{
     path::ToPath,
-    persistable::{MsgPackSerializable, ResolvablePersistable},
+    persistable::{MsgPackDeserializable, MsgPackSerializable, ResolvablePersistable},
+    validator::ValidatorModel,
+    DeserializeOwned,
 };
 
-use crate::types::{
-    block_state::BlockState,
-    consolidated_block::ConsolidatedBlock,
-    consolidated_epoch::{AggregatedEpochData, ConsolidatedEpoch},
+use crate::{
+    beacon_chain::checkpoint::Checkpoint,
+    types::{
+        block_state::BlockState,
+        consolidated_block::{AttestationCorrectness, ConsolidatedBlock, OperationIds},
+        consolidated_epoch::{AggregatedEpochData, ConsolidatedEpoch},
+        consolidated_sync_committees::ConsolidatedSyncCommittees,
+        consolidated_validator::ValidatorSnapshot,
+    },
 };
 
+/// Bumped whenever the persisted fields of `IndexingState` change
+const INDEXING_STATE_VERSION: u32 = 1;
+
+/// How many slots can be rolled back when the head is reorged, unless configured otherwise
+pub const DEFAULT_REORG_DEPTH: usize = 8;
+
+/// The indexing state right before processing `block`
+struct RetainedState<E: EthSpec> {
+    beacon_state: BeaconState<E>,
+    aggregated_epoch_data: AggregatedEpochData,
+    latest_block_root: Option<Hash256>,
+    operation_ids: OperationIds,
+    /// The validator snapshots replaced by `block`, when it crossed an epoch boundary
+    validator_snapshots: Option<Vec<ValidatorSnapshot>>,
+    block: BlockState<E>,
+}
+
+/// Written before the indexing state so that older states can be migrated
+#[derive(Serialize, Deserialize)]
+struct IndexingStateHeader {
+    version: u32,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct IndexingState<E: EthSpec> {
     is_genesis: bool,
@@ -30,22 +75,95 @@ pub struct IndexingState<E: EthSpec> {
     #[serde(serialize_with = "serialize_deposit_cache")]
     #[serde(deserialize_with = "deserialize_deposit_cache")]
     deposit_cache: DepositCache,
-    #[serde(skip_serializing)]
-    #[serde(deserialize_with = "spec::<_, E>")]
+    #[serde(default)]
+    anchor_slot: Option<Slot>,
+    #[serde(default)]
+    latest_block_root: Option<Hash256>,
+    #[serde(default)]
+    operation_ids: OperationIds,
+    #[serde(skip)]
+    retained_states: VecDeque<RetainedState<E>>,
+    /// How many states are retained at most. The ones before the finalized checkpoint aren't.
+    #[serde(skip, default = "default_reorg_depth")]
+    reorg_depth: usize,
+    /// The validators at the latest epoch boundary. Every validator is seen as changed after a restart.
+    #[serde(skip)]
+    validator_snapshots: Vec<ValidatorSnapshot>,
+    #[serde(skip, default = "E::default_spec")]
     pub(super) spec: ChainSpec,
 }
 
+/// A copy of the persisted fields of an `IndexingState`, to serialize them without holding the
+/// lock on the state. It's serialized like the state, so that it's deserialized as one.
+#[derive(Serialize)]
+pub struct IndexingStateSnapshot<E: EthSpec> {
+    is_genesis: bool,
+    aggregated_epoch_data: AggregatedEpochData,
+    beacon_state: BeaconState<E>,
+    #[serde(serialize_with = "serialize_ssz_deposit_cache")]
+    deposit_cache: SszDepositCache,
+    anchor_slot: Option<Slot>,
+    latest_block_root: Option<Hash256>,
+    operation_ids: OperationIds,
+}
+
+impl<E: EthSpec> IndexingStateSnapshot<E> {
+    pub fn latest_slot(&self) -> Option<Slot> {
+        if self.is_genesis {
+            None
+        } else {
+            Some(self.beacon_state.slot())
+        }
+    }
+}
+
 impl<E: EthSpec> IndexingState<E> {
-    pub fn new(genesis_state: BeaconState<E>, deposit_contract_deploy_block: u64) -> Self {
+    pub fn new(
+        genesis_state: BeaconState<E>,
+        deposit_contract_deploy_block: u64,
+        spec: ChainSpec,
+    ) -> Self {
         Self {
             is_genesis: true,
             aggregated_epoch_data: AggregatedEpochData::default(),
             beacon_state: genesis_state,
             deposit_cache: DepositCache::new(deposit_contract_deploy_block),
-            spec: E::default_spec(),
+            anchor_slot: None,
+            latest_block_root: None,
+            operation_ids: OperationIds::default(),
+            retained_states: VecDeque::new(),
+            reorg_depth: DEFAULT_REORG_DEPTH,
+            validator_snapshots: vec![],
+            spec,
         }
     }
 
+    pub fn from_checkpoint(
+        checkpoint: &Checkpoint<E>,
+        deposit_contract_deploy_block: u64,
+        spec: ChainSpec,
+    ) -> Self {
+        Self {
+            is_genesis: false,
+            aggregated_epoch_data: AggregatedEpochData::default(),
+            beacon_state: checkpoint.state.clone(),
+            deposit_cache: DepositCache::new(deposit_contract_deploy_block),
+            anchor_slot: Some(checkpoint.slot()),
+            latest_block_root: Some(checkpoint.block.canonical_root()),
+            operation_ids: OperationIds::default(),
+            retained_states: VecDeque::new(),
+            reorg_depth: DEFAULT_REORG_DEPTH,
+            validator_snapshots: vec![],
+            spec,
+        }
+    }
+
+    /// The spec isn't serialized, so it has to be restored once the state is deserialized.
+    pub fn with_spec(mut self, spec: ChainSpec) -> Self {
+        self.spec = spec;
+        self
+    }
+
     pub fn latest_slot(&self) -> Option<Slot> {
         if self.is_genesis {
             None
@@ -54,6 +172,90 @@ impl<E: EthSpec> IndexingState<E> {
         }
     }
 
+    pub fn snapshot(&self) -> IndexingStateSnapshot<E> {
+        IndexingStateSnapshot {
+            is_genesis: self.is_genesis,
+            aggregated_epoch_data: self.aggregated_epoch_data.clone(),
+            beacon_state: self.beacon_state.clone(),
+            deposit_cache: SszDepositCache::from_deposit_cache(&self.deposit_cache),
+            anchor_slot: self.anchor_slot,
+            latest_block_root: self.latest_block_root,
+            operation_ids: self.operation_ids,
+        }
+    }
+
+    /// The slot the indexing started from, if it didn't start from genesis
+    pub fn anchor_slot(&self) -> Option<Slot> {
+        self.anchor_slot
+    }
+
+    pub fn is_before_anchor(&self, slot: Slot) -> bool {
+        self.anchor_slot.map_or(false, |anchor_slot| slot < anchor_slot)
+    }
+
+    /// Sets how many slots can be rolled back at most
+    pub fn with_reorg_depth(mut self, reorg_depth: usize) -> Self {
+        self.reorg_depth = reorg_depth;
+        self
+    }
+
+    pub fn reorg_depth(&self) -> usize {
+        self.reorg_depth
+    }
+
+    pub fn latest_block_root(&self) -> Option<Hash256> {
+        self.latest_block_root
+    }
+
+    /// The ids of the next operations of the indexed chain
+    pub fn operation_ids(&self) -> OperationIds {
+        self.operation_ids
+    }
+
+    pub fn is_retained_block(&self, root: Hash256) -> bool {
+        self.retained_states
+            .iter()
+            .any(|retained_state| retained_state.block.root() == Some(root))
+    }
+
+    /// Rolls back to the latest retained state before `slot` whose head is the block `root`,
+    /// returning the blocks which aren't canonical anymore.
+    pub fn rollback_to(
+        &mut self,
+        root: Hash256,
+        slot: Slot,
+    ) -> Option<Vec<Arc<SignedBeaconBlock<E>>>> {
+        let position = self.retained_states.iter().rposition(|retained_state| {
+            retained_state.latest_block_root == Some(root)
+                && retained_state.beacon_state.slot() < slot
+        })?;
+
+        let mut displaced_states = self.retained_states.split_off(position);
+
+        let displaced_blocks = displaced_states
+            .iter()
+            .filter_map(|retained_state| retained_state.block.canonical_block().cloned())
+            .collect();
+
+        // The earliest replaced snapshots are the ones before the rolled back blocks
+        let validator_snapshots = displaced_states
+            .iter_mut()
+            .find_map(|retained_state| retained_state.validator_snapshots.take());
+
+        let retained_state = displaced_states.pop_front()?;
+
+        self.beacon_state = retained_state.beacon_state;
+        self.aggregated_epoch_data = retained_state.aggregated_epoch_data;
+        self.latest_block_root = retained_state.latest_block_root;
+        self.operation_ids = retained_state.operation_ids;
+
+        if let Some(validator_snapshots) = validator_snapshots {
+            self.validator_snapshots = validator_snapshots;
+        }
+
+        Some(displaced_blocks)
+    }
+
     pub fn latest_deposit_block(&self) -> u64 {
         self.deposit_cache.latest_block_number()
     }
@@ -68,9 +270,12 @@ impl<E: EthSpec> IndexingState<E> {
     pub fn process_block(
         &mut self,
         block: BlockState<E>,
+        clock: &Clock,
     ) -> Result<(ConsolidatedBlock<E>, Option<ConsolidatedEpoch<E>>), String> {
         let slot = block.slot();
         let mut beacon_state = self.beacon_state.clone();
+        let mut aggregated_epoch_data = self.aggregated_epoch_data.clone();
+        let mut validator_snapshots = None;
         let mut consensus_context = ConsensusContext::new(block.slot());
 
         let summary = match &block {
@@ -100,15 +305,29 @@ impl<E: EthSpec> IndexingState<E> {
         };
 
         let consolidated_epoch = summary.map(|s| {
+            let epoch = beacon_state.previous_epoch();
+            let (changed_validators, snapshots) = self.changed_validators(&beacon_state);
+
+            validator_snapshots = Some(snapshots);
+
             ConsolidatedEpoch::new(
-                beacon_state.previous_epoch(),
-                self.aggregated_epoch_data.aggregate(),
+                epoch,
+                clock
+                    .timestamp(epoch.start_slot(E::slots_per_epoch()))
+                    .unwrap_or(0),
+                aggregated_epoch_data.aggregate(),
                 &s,
                 beacon_state.balances().to_owned().into(),
+                beacon_state
+                    .validators()
+                    .iter()
+                    .map(|validator| validator.effective_balance)
+                    .collect(),
+                changed_validators,
             )
         });
 
-        self.aggregated_epoch_data.consolidate(&block);
+        aggregated_epoch_data.consolidate(&block);
 
         let committees = if slot == 0 {
             beacon_state
@@ -128,21 +347,174 @@ impl<E: EthSpec> IndexingState<E> {
                 .collect()
         };
 
+        let attestations_correctness = attestations_correctness(&beacon_state, &block);
+        let sync_committees = sync_committees(&mut beacon_state, &self.spec)?;
+
         let consolidated_block = ConsolidatedBlock::new(
             block,
+            clock.timestamp(slot).unwrap_or(0),
             consensus_context
                 .get_proposer_index(&beacon_state, &self.spec)
                 .map_err(|err| format!("Error while processing proposer: {err:?}"))?,
             committees,
+            attestations_correctness,
+            sync_committees,
             vec![],
-        );
+        )
+        .with_first_operation_ids(self.operation_ids);
+
+        // The previous state is moved to the retained ones rather than cloned
+        let retained_state = RetainedState {
+            beacon_state: mem::replace(&mut self.beacon_state, beacon_state),
+            aggregated_epoch_data: mem::replace(
+                &mut self.aggregated_epoch_data,
+                aggregated_epoch_data,
+            ),
+            latest_block_root: self.latest_block_root,
+            operation_ids: mem::replace(
+                &mut self.operation_ids,
+                consolidated_block.next_operation_ids(),
+            ),
+            validator_snapshots: validator_snapshots
+                .map(|snapshots| mem::replace(&mut self.validator_snapshots, snapshots)),
+            block: consolidated_block.block().clone(),
+        };
+
+        self.retain_state(retained_state);
 
-        self.beacon_state = beacon_state;
         self.is_genesis = false;
 
+        if let Some(root) = consolidated_block.root() {
+            self.latest_block_root = Some(root);
+        }
+
         Ok((consolidated_block, consolidated_epoch))
     }
 
+    pub fn process_anchor_block(
+        &mut self,
+        block: Arc<SignedBeaconBlock<E>>,
+        clock: &Clock,
+    ) -> Result<ConsolidatedBlock<E>, String> {
+        let slot = block.slot();
+        let proposer_index = block.message().proposer_index();
+
+        let committees = self
+            .beacon_state
+            .get_beacon_committees_at_slot(slot)
+            .map_err(|err| format!("Error while processing committees: {err:?}"))?
+            .into_iter()
+            .map(|c| c.into_owned())
+            .collect();
+
+        let block = BlockState::Proposed(block);
+
+        self.aggregated_epoch_data.consolidate(&block);
+
+        let attestations_correctness = attestations_correctness(&self.beacon_state, &block);
+        let sync_committees = sync_committees(&mut self.beacon_state, &self.spec)?;
+
+        let consolidated_block = ConsolidatedBlock::new(
+            block,
+            clock.timestamp(slot).unwrap_or(0),
+            proposer_index,
+            committees,
+            attestations_correctness,
+            sync_committees,
+            vec![],
+        )
+        .with_first_operation_ids(self.operation_ids);
+
+        self.operation_ids = consolidated_block.next_operation_ids();
+
+        Ok(consolidated_block)
+    }
+
+    /// Consolidates a non-canonical block found by its root. It's only kept when the canonical
+    /// chain missed its slot, along with the epoch to update if it has already been aggregated.
+    pub fn process_orphaned_block(
+        &mut self,
+        block: Arc<SignedBeaconBlock<E>>,
+        clock: &Clock,
+    ) -> Option<(ConsolidatedBlock<E>, Option<Epoch>)> {
+        let slot = block.slot();
+
+        if self.is_missed_slot(slot) != Some(true) {
+            return None;
+        }
+
+        let epoch = slot.epoch(E::slots_per_epoch());
+
+        let aggregated_epoch = if epoch == self.beacon_state.current_epoch() {
+            self.aggregated_epoch_data.orphan_missed_block();
+            None
+        } else {
+            Some(epoch)
+        };
+
+        let consolidated_block =
+            ConsolidatedBlock::orphaned(block, clock.timestamp(slot).unwrap_or(0));
+
+        Some((consolidated_block, aggregated_epoch))
+    }
+
+    /// The validators whose model differs from the latest epoch boundary, along with their new
+    /// model, and the snapshots to compare the next epoch boundary with
+    fn changed_validators(
+        &self,
+        beacon_state: &BeaconState<E>,
+    ) -> (Vec<(u64, ValidatorModel)>, Vec<ValidatorSnapshot>) {
+        let current_epoch = beacon_state.current_epoch();
+
+        let validator_snapshots = beacon_state
+            .validators()
+            .iter()
+            .zip(beacon_state.balances().iter())
+            .map(|(validator, balance)| {
+                ValidatorSnapshot::new::<E>(validator, current_epoch, *balance)
+            })
+            .collect::<Vec<_>>();
+
+        let changed_validators = validator_snapshots
+            .iter()
+            .enumerate()
+            .filter(|(i, snapshot)| self.validator_snapshots.get(*i) != Some(*snapshot))
+            .map(|(i, snapshot)| (i as u64, snapshot.to_model::<E>(current_epoch)))
+            .collect();
+
+        (changed_validators, validator_snapshots)
+    }
+
+    /// Retains the state, dropping the ones past the reorg depth and the ones before the
+    /// finalized checkpoint, which can't be rolled back to
+    fn retain_state(&mut self, retained_state: RetainedState<E>) {
+        let finalized_slot = self
+            .beacon_state
+            .finalized_checkpoint()
+            .epoch
+            .start_slot(E::slots_per_epoch());
+
+        self.retained_states.push_back(retained_state);
+        self.retained_states
+            .retain(|retained_state| retained_state.beacon_state.slot() >= finalized_slot);
+
+        while self.retained_states.len() > self.reorg_depth {
+            self.retained_states.pop_front();
+        }
+    }
+
+    /// Whether the canonical chain has no block at `slot`, unknown for slots out of the state history
+    fn is_missed_slot(&self, slot: Slot) -> Option<bool> {
+        if slot == 0 || slot >= self.beacon_state.slot() {
+            return None;
+        }
+
+        let root = self.beacon_state.get_block_root(slot).ok()?;
+        let previous_root = self.beacon_state.get_block_root(slot - 1).ok()?;
+
+        Some(root == previous_root)
+    }
+
     pub fn insert_deposits(
         &mut self,
         deposits_logs: Vec<DepositLog>,
@@ -194,12 +566,95 @@ impl<E: EthSpec> IndexingState<E> {
     }
 }
 
-impl<E: EthSpec + Serialize> MsgPackSerializable for IndexingState<E> {}
+/// Writes the indexing state, or a snapshot of it, after its header
+fn write_versioned<T: Serialize, W: Write>(
+    indexing_state: &T,
+    writer: &mut W,
+) -> Result<(), String> {
+    let mut serializer = rmp_serde::Serializer::new(writer);
+
+    IndexingStateHeader {
+        version: INDEXING_STATE_VERSION,
+    }
+    .serialize(&mut serializer)
+    .map_err(|err| format!("Can't serialize the indexing state header: {err}"))?;
+
+    indexing_state
+        .serialize(&mut serializer)
+        .map_err(|err| format!("Can't serialize the indexing state: {err}"))
+}
+
+fn write_versioned_file<T: Serialize>(indexing_state: &T, full_path: &str) -> Result<(), String> {
+    let file =
+        File::create(full_path).map_err(|err| format!("Can't create file '{full_path}': {err}"))?;
+    let mut writer = BufWriter::new(file);
+
+    write_versioned(indexing_state, &mut writer)?;
+
+    writer
+        .flush()
+        .map_err(|err| format!("Can't write '{full_path}': {err}"))
+}
+
+impl<E: EthSpec + DeserializeOwned> IndexingState<E> {
+    fn read_versioned(bytes: &[u8]) -> Result<Self, String> {
+        let mut deserializer = rmp_serde::Deserializer::new(bytes);
+
+        let header = match IndexingStateHeader::deserialize(&mut deserializer) {
+            Ok(header) => header,
+            Err(_) => return Self::read_unversioned(bytes),
+        };
+
+        // Older versions are migrated here when the persisted fields change
+        match header.version {
+            INDEXING_STATE_VERSION => Self::deserialize(&mut deserializer)
+                .map_err(|err| format!("Can't deserialize the indexing state: {err}")),
+            version => Err(format!("Unsupported indexing state version {version}")),
+        }
+    }
+
+    /// The states written before the header start with the same fields, and the ones added since
+    /// have defaults
+    fn read_unversioned(bytes: &[u8]) -> Result<Self, String> {
+        let indexing_state = Self::deserialize(&mut rmp_serde::Deserializer::new(bytes))
+            .map_err(|err| format!("Can't deserialize the unversioned indexing state: {err}"))?;
+
+        warn!("Migrated an indexing state written before its version header");
+
+        Ok(indexing_state)
+    }
+}
+
+impl<E: EthSpec + Serialize> MsgPackSerializable for IndexingState<E> {
+    fn serialize_to_file(&self, full_path: &str) -> Result<(), String> {
+        write_versioned_file(self, full_path)
+    }
+}
+
+impl<E: EthSpec + Serialize> MsgPackSerializable for IndexingStateSnapshot<E> {
+    fn serialize_to_file(&self, full_path: &str) -> Result<(), String> {
+        write_versioned_file(self, full_path)
+    }
+}
+
+impl<E: EthSpec + DeserializeOwned> MsgPackDeserializable for IndexingState<E> {
+    fn deserialize_from_file(full_path: &str) -> Result<Self, String> {
+        let bytes =
+            fs::read(full_path).map_err(|err| format!("Can't read '{full_path}': {err}"))?;
+
+        Self::read_versioned(&bytes).map_err(|err| format!("{full_path}: {err}"))
+    }
+}
 
 impl<E: EthSpec + Serialize> ResolvablePersistable for IndexingState<E> {
+    /// Renamed once fully written, so that a crash while saving keeps the previous state intact
     fn save(&self, base_path: &str) -> Result<(), String> {
         let full_path = Self::to_path(base_path, &());
-        self.serialize_to_file(&full_path)
+        let tmp_path = format!("{full_path}.tmp");
+
+        self.serialize_to_file(&tmp_path)?;
+
+        fs::rename(&tmp_path, &full_path).map_err(|err| format!("Can't rename '{tmp_path}': {err}"))
     }
 }
 
@@ -211,11 +666,66 @@ impl<E: EthSpec> ToPath for IndexingState<E> {
     }
 }
 
-fn spec<'de, D, E: EthSpec>(_: D) -> Result<ChainSpec, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    Ok(E::default_spec())
+/// Compares the votes of the block attestations with the block roots of the state it produced
+fn attestations_correctness<E: EthSpec>(
+    beacon_state: &BeaconState<E>,
+    block: &BlockState<E>,
+) -> Vec<AttestationCorrectness> {
+    let block = match block.canonical_block() {
+        Some(block) => block,
+        None => return vec![],
+    };
+
+    block
+        .message()
+        .body()
+        .attestations()
+        .iter()
+        .map(|attestation| AttestationCorrectness {
+            target: beacon_state
+                .get_block_root_at_epoch(attestation.data.target.epoch)
+                .ok()
+                == Some(attestation.data.target.root),
+            head: beacon_state.get_block_root(attestation.data.slot).ok()
+                == Some(&attestation.data.beacon_block_root),
+        })
+        .collect()
+}
+
+/// The sync committees of the state, which only exist from Altair
+fn sync_committees<E: EthSpec>(
+    beacon_state: &mut BeaconState<E>,
+    spec: &ChainSpec,
+) -> Result<Option<ConsolidatedSyncCommittees>, String> {
+    let (current, next) = match (
+        beacon_state.current_sync_committee(),
+        beacon_state.next_sync_committee(),
+    ) {
+        (Ok(current), Ok(next)) => (current.clone(), next.clone()),
+        _ => return Ok(None),
+    };
+
+    let period = beacon_state
+        .current_epoch()
+        .sync_committee_period(spec)
+        .map_err(|err| format!("Error while computing the sync committee period: {err:?}"))?;
+
+    let mut indices = |sync_committee: &SyncCommittee<E>| {
+        beacon_state
+            .get_sync_committee_indices(sync_committee)
+            .map(|indices| indices.into_iter().map(|i| i as u64).collect())
+            .map_err(|err| format!("Error while processing the sync committee: {err:?}"))
+    };
+
+    Ok(Some(ConsolidatedSyncCommittees {
+        period,
+        current: indices(current.as_ref())?,
+        next: indices(next.as_ref())?,
+    }))
+}
+
+fn default_reorg_depth() -> usize {
+    DEFAULT_REORG_DEPTH
 }
 
 fn serialize_deposit_cache<S>(
@@ -227,15 +737,17 @@ where
 {
     let ssz_deposit_cache = SszDepositCache::from_deposit_cache(deposit_cache);
 
-    let bytes = ssz_deposit_cache.as_ssz_bytes();
-
-    let mut seq = serializer.serialize_seq(Some(bytes.len()))?;
-
-    for b in bytes {
-        seq.serialize_element(&b)?
-    }
+    serialize_ssz_deposit_cache(&ssz_deposit_cache, serializer)
+}
 
-    seq.end()
+fn serialize_ssz_deposit_cache<S>(
+    ssz_deposit_cache: &SszDepositCache,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_bytes(&ssz_deposit_cache.as_ssz_bytes())
 }
 
 fn deserialize_deposit_cache<'de, D>(deserializer: D) -> Result<DepositCache, D::Error>
@@ -251,13 +763,11 @@ where
             formatter.write_str("a ssz deposit cache")
         }
 
-        fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+        fn visit_bytes<Err>(self, bytes: &[u8]) -> Result<Self::Value, Err>
         where
-            A: serde::de::SeqAccess<'de>,
+            Err: serde::de::Error,
         {
-            let bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
-
-            let ssz_deposit_cache = SszDepositCache::from_ssz_bytes(&bytes).map_err(|err| {
+            let ssz_deposit_cache = SszDepositCache::from_ssz_bytes(bytes).map_err(|err| {
                 serde::de::Error::custom(format!("Failed to decode deposit cache: {:?}", err))
             })?;
 
@@ -265,22 +775,65 @@ where
                 .to_deposit_cache()
                 .map_err(serde::de::Error::custom)
         }
+
+        /// The states written before the header encoded the deposit cache as a sequence of bytes
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+
+            while let Some(byte) = seq.next_element::<u8>()? {
+                bytes.push(byte);
+            }
+
+            self.visit_bytes(&bytes)
+        }
     }
 
-    deserializer.deserialize_seq(DepositCacheVisitor)
+    deserializer.deserialize_bytes(DepositCacheVisitor)
 }
 
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
 
-    use lighthouse_types::{MainnetEthSpec, Slot};
+    use eth1::{DepositCache, DepositLog, SszDepositCache};
+    use lighthouse_types::{
+        BeaconState, DepositData, EthSpec, Hash256, MainnetEthSpec, PublicKeyBytes, SignatureBytes,
+        Slot,
+    };
+    use serde::{Serialize, Serializer};
+    use shared::utils::clock::Clock;
+    use ssz::Encode;
 
     use crate::{
-        beacon_chain::beacon_context::BeaconContext, db::indexing_state::IndexingState,
-        test_utils::BeaconChainHarness, types::block_state::BlockState,
+        beacon_chain::beacon_context::{load_eth2_network_config, BeaconContext},
+        db::indexing_state::{write_versioned, IndexingState},
+        test_utils::BeaconChainHarness,
+        types::{block_state::BlockState, consolidated_epoch::AggregatedEpochData},
     };
 
+    /// The indexing state as written before it was versioned
+    #[derive(Serialize)]
+    struct LegacyIndexingState<'a, E: EthSpec> {
+        is_genesis: bool,
+        aggregated_epoch_data: &'a AggregatedEpochData,
+        beacon_state: &'a BeaconState<E>,
+        #[serde(serialize_with = "serialize_legacy_deposit_cache")]
+        deposit_cache: &'a DepositCache,
+    }
+
+    fn serialize_legacy_deposit_cache<S>(
+        deposit_cache: &&DepositCache,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(SszDepositCache::from_deposit_cache(deposit_cache).as_ssz_bytes())
+    }
+
     #[tokio::test]
     async fn test_genesis_deposits() {
         let harness = BeaconChainHarness::new();
@@ -288,16 +841,97 @@ mod tests {
         assert_eq!(harness.state().validators().len(), 2);
     }
 
+    #[tokio::test]
+    async fn test_versioned_round_trip() {
+        let harness = BeaconChainHarness::new();
+        let mut indexing_state =
+            IndexingState::<MainnetEthSpec>::new(harness.state(), 1234, harness.spec());
+
+        indexing_state
+            .insert_deposits(vec![deposit_log(0), deposit_log(1)])
+            .unwrap();
+
+        let mut bytes = vec![];
+        write_versioned(&indexing_state, &mut bytes).unwrap();
+
+        let mut restored =
+            IndexingState::<MainnetEthSpec>::read_versioned(bytes.as_slice()).unwrap();
+
+        assert_eq!(
+            restored.beacon_state.slot(),
+            indexing_state.beacon_state.slot()
+        );
+        assert_eq!(
+            restored.deposit_cache.latest_block_number(),
+            indexing_state.deposit_cache.latest_block_number()
+        );
+        assert_eq!(restored.deposit_cache.len(), 2);
+        assert_eq!(
+            restored.get_deposits(0..2).unwrap(),
+            indexing_state.get_deposits(0..2).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unversioned_migration() {
+        let harness = BeaconChainHarness::new();
+        let mut indexing_state =
+            IndexingState::<MainnetEthSpec>::new(harness.state(), 1234, harness.spec());
+
+        indexing_state
+            .insert_deposits(vec![deposit_log(0)])
+            .unwrap();
+
+        let mut bytes = vec![];
+        LegacyIndexingState {
+            is_genesis: indexing_state.is_genesis,
+            aggregated_epoch_data: &indexing_state.aggregated_epoch_data,
+            beacon_state: &indexing_state.beacon_state,
+            deposit_cache: &indexing_state.deposit_cache,
+        }
+        .serialize(&mut rmp_serde::Serializer::new(&mut bytes))
+        .unwrap();
+
+        let restored = IndexingState::<MainnetEthSpec>::read_versioned(bytes.as_slice()).unwrap();
+
+        assert_eq!(restored.latest_slot(), None);
+        assert_eq!(restored.anchor_slot(), None);
+        assert_eq!(restored.deposit_cache.len(), 1);
+    }
+
+    fn deposit_log(index: u64) -> DepositLog {
+        DepositLog {
+            deposit_data: DepositData {
+                pubkey: PublicKeyBytes::empty(),
+                withdrawal_credentials: Hash256::from_low_u64_be(index),
+                amount: 32_000_000_000,
+                signature: SignatureBytes::empty(),
+            },
+            block_number: 1234 + index,
+            index,
+            signature_is_valid: true,
+        }
+    }
+
     #[tokio::test]
     async fn test_contains_block_root() {
         let mut harness = BeaconChainHarness::new();
-        let beacon_context =
-            BeaconContext::<MainnetEthSpec>::new(harness.state(), harness.spec()).unwrap();
+        let beacon_context = BeaconContext::<MainnetEthSpec>::new(
+            harness.state(),
+            load_eth2_network_config("mainnet").unwrap(),
+            harness.spec(),
+        )
+        .unwrap();
+        let clock = Clock::new(
+            beacon_context.genesis_state.genesis_time(),
+            &beacon_context.spec,
+        );
         let mut indexing_state = IndexingState::new(
             beacon_context.genesis_state,
             beacon_context
                 .eth2_network_config
                 .deposit_contract_deploy_block,
+            beacon_context.spec,
         );
 
         let at_0 = Arc::new(harness.make_block(0).await);
@@ -306,16 +940,16 @@ mod tests {
         let at_3 = Arc::new(harness.make_block(3).await);
 
         indexing_state
-            .process_block(BlockState::Proposed(at_0.clone()))
+            .process_block(BlockState::Proposed(at_0.clone()), &clock)
             .unwrap();
         indexing_state
-            .process_block(BlockState::Proposed(at_1.clone()))
+            .process_block(BlockState::Proposed(at_1.clone()), &clock)
             .unwrap();
         indexing_state
-            .process_block(BlockState::Proposed(at_2.clone()))
+            .process_block(BlockState::Proposed(at_2.clone()), &clock)
             .unwrap();
         indexing_state
-            .process_block(BlockState::Proposed(at_3.clone()))
+            .process_block(BlockState::Proposed(at_3.clone()), &clock)
             .unwrap();
 
         indexing_state
@@ -344,4 +978,130 @@ mod tests {
             indexing_state.beacon_state.get_block_root(Slot::new(3))
         );
     }
+
+    #[tokio::test]
+    async fn test_rollback_to() {
+        let mut harness = BeaconChainHarness::new();
+        let clock = Clock::new(harness.state().genesis_time(), &harness.spec());
+        let mut indexing_state =
+            IndexingState::<MainnetEthSpec>::new(harness.state(), 0, harness.spec());
+
+        let at_0 = Arc::new(harness.make_block(0).await);
+        let at_1 = Arc::new(harness.make_block(1).await);
+        let state_at_1 = harness.state();
+        let at_2 = Arc::new(harness.make_block(2).await);
+        let at_3 = Arc::new(harness.make_block(3).await);
+        let fork_at_3 = Arc::new(harness.make_fork_block(state_at_1, 3).await);
+
+        for block in vec![&at_0, &at_1, &at_2, &at_3] {
+            indexing_state
+                .process_block(BlockState::Proposed(block.clone()), &clock)
+                .unwrap();
+        }
+
+        assert!(indexing_state
+            .rollback_to(Hash256::zero(), fork_at_3.slot())
+            .is_none());
+
+        let displaced_blocks = indexing_state
+            .rollback_to(at_1.canonical_root(), fork_at_3.slot())
+            .unwrap();
+
+        assert_eq!(
+            displaced_blocks
+                .iter()
+                .map(|block| block.canonical_root())
+                .collect::<Vec<_>>(),
+            vec![at_2.canonical_root(), at_3.canonical_root()]
+        );
+        assert_eq!(indexing_state.latest_slot(), Some(Slot::new(1)));
+        assert_eq!(
+            indexing_state.latest_block_root(),
+            Some(at_1.canonical_root())
+        );
+        assert!(indexing_state.is_retained_block(at_1.canonical_root()));
+        assert!(!indexing_state.is_retained_block(at_2.canonical_root()));
+
+        indexing_state
+            .process_block(BlockState::Proposed(fork_at_3.clone()), &clock)
+            .unwrap();
+
+        assert_eq!(indexing_state.latest_slot(), Some(Slot::new(3)));
+        assert_eq!(
+            indexing_state.latest_block_root(),
+            Some(fork_at_3.canonical_root())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rollback_to_restores_validator_snapshots() {
+        let mut harness = BeaconChainHarness::new();
+        let clock = Clock::new(harness.state().genesis_time(), &harness.spec());
+        let mut indexing_state =
+            IndexingState::<MainnetEthSpec>::new(harness.state(), 0, harness.spec());
+
+        let at_0 = Arc::new(harness.make_block(0).await);
+
+        indexing_state
+            .process_block(BlockState::Proposed(at_0.clone()), &clock)
+            .unwrap();
+
+        for slot in 1..MainnetEthSpec::slots_per_epoch() {
+            indexing_state
+                .process_block(BlockState::Missed(Slot::new(slot)), &clock)
+                .unwrap();
+        }
+
+        let validator_snapshots = indexing_state.validator_snapshots.clone();
+        let epoch_boundary = Slot::new(MainnetEthSpec::slots_per_epoch());
+
+        let (_, epoch) = indexing_state
+            .process_block(BlockState::Missed(epoch_boundary), &clock)
+            .unwrap();
+
+        assert!(epoch.is_some());
+        assert!(indexing_state
+            .retained_states
+            .back()
+            .unwrap()
+            .validator_snapshots
+            .is_some());
+
+        // Tells the restored snapshots apart from the ones of the rolled back epoch boundary
+        indexing_state.validator_snapshots.clear();
+
+        let displaced_blocks = indexing_state
+            .rollback_to(at_0.canonical_root(), epoch_boundary)
+            .unwrap();
+
+        assert!(displaced_blocks.is_empty());
+        assert_eq!(indexing_state.latest_slot(), Some(epoch_boundary - 1));
+        assert_eq!(indexing_state.validator_snapshots, validator_snapshots);
+    }
+
+    #[tokio::test]
+    async fn test_reorg_depth() {
+        let mut harness = BeaconChainHarness::new();
+        let clock = Clock::new(harness.state().genesis_time(), &harness.spec());
+        let mut indexing_state =
+            IndexingState::<MainnetEthSpec>::new(harness.state(), 0, harness.spec())
+                .with_reorg_depth(2);
+
+        let at_0 = Arc::new(harness.make_block(0).await);
+        let at_1 = Arc::new(harness.make_block(1).await);
+
+        indexing_state
+            .process_block(BlockState::Proposed(at_0.clone()), &clock)
+            .unwrap();
+        indexing_state
+            .process_block(BlockState::Proposed(at_1.clone()), &clock)
+            .unwrap();
+        indexing_state
+            .process_block(BlockState::Missed(Slot::new(2)), &clock)
+            .unwrap();
+
+        assert_eq!(indexing_state.retained_states.len(), 2);
+        assert!(!indexing_state.is_retained_block(at_0.canonical_root()));
+        assert!(indexing_state.is_retained_block(at_1.canonical_root()));
+    }
 }
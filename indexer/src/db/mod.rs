@@ -1,29 +1,43 @@
 use lighthouse_types::{BeaconState, ChainSpec, EthSpec};
 use parking_lot::{
-    MappedRwLockReadGuard, MappedRwLockWriteGuard, RwLock, RwLockReadGuard, RwLockWriteGuard,
+    MappedRwLockReadGuard, MappedRwLockWriteGuard, Mutex, MutexGuard, RwLock, RwLockReadGuard,
+    RwLockWriteGuard,
 };
+use shared::utils::clock::Clock;
 use std::convert::TryFrom;
+use std::path::Path;
 use std::sync::Arc;
+use tracing::{error, info};
 use types::{
     block_request::BlockRequestModelWithId,
     block_root::BlockRootModel,
     committee::CommitteeModel,
     deposit::ExecutionLayerDepositModel,
     meta::{DepositMeta, Meta},
-    path::FromPath,
+    path::ToPath,
+    persistable::MsgPackDeserializable,
     utils::{MetaCache, ModelCache},
     validator::{ValidatorExtendedModel, ValidatorModel},
     DeserializeOwned,
 };
 
-use self::indexing_state::IndexingState;
+use crate::beacon_chain::checkpoint::Checkpoint;
 
 mod block_by_root_requests;
+pub mod graffiti_index;
 mod indexing_state;
+mod orphaned_blocks;
 mod peer_db;
+pub mod search_index;
+pub mod snapshots;
+mod sortable_indexes;
+pub mod validator_lookups;
 
 pub use block_by_root_requests::BlockByRootRequests;
+pub use indexing_state::{IndexingState, IndexingStateSnapshot, DEFAULT_REORG_DEPTH};
+pub use orphaned_blocks::OrphanedBlocks;
 pub use peer_db::PeerDb;
+pub use sortable_indexes::{PendingSortables, SortableIndexes};
 
 pub struct Stores<E: EthSpec> {
     indexing_state: RwLock<IndexingState<E>>,
@@ -33,6 +47,9 @@ pub struct Stores<E: EthSpec> {
     validators_cache: Arc<RwLock<ModelCache<ValidatorModel>>>,
     validators_extended_cache: Arc<RwLock<ModelCache<ValidatorExtendedModel>>>,
     meta_cache: Arc<RwLock<MetaCache>>,
+    sortable_indexes: SortableIndexes,
+    orphaned_blocks: Mutex<OrphanedBlocks>,
+    clock: Clock,
 }
 
 impl<E: EthSpec + DeserializeOwned> Stores<E> {
@@ -41,9 +58,28 @@ impl<E: EthSpec + DeserializeOwned> Stores<E> {
         genesis_state: BeaconState<E>,
         deposit_contract_deploy_block: u64,
         block_requests: Vec<BlockRequestModelWithId>,
+        checkpoint: Option<&Checkpoint<E>>,
+        reorg_depth: usize,
+        spec: ChainSpec,
     ) -> Self {
-        let indexing_state = IndexingState::from_path(&base_dir, &())
-            .unwrap_or_else(|_| IndexingState::new(genesis_state, deposit_contract_deploy_block));
+        let clock = Clock::new(genesis_state.genesis_time(), &spec);
+        let indexing_state_path = IndexingState::<E>::to_path(&base_dir, &());
+        let indexing_state = load_indexing_state(&indexing_state_path)
+            .into_iter()
+            .chain(snapshots::load_latest(&base_dir, &spec))
+            .max_by_key(|indexing_state| indexing_state.latest_slot())
+            .map(|indexing_state| indexing_state.with_spec(spec.clone()))
+            .unwrap_or_else(|| match checkpoint {
+                Some(checkpoint) => {
+                    info!(slot = %checkpoint.slot(), "Indexing from the checkpoint");
+                    IndexingState::from_checkpoint(checkpoint, deposit_contract_deploy_block, spec)
+                }
+                None => {
+                    info!("Indexing from genesis");
+                    IndexingState::new(genesis_state, deposit_contract_deploy_block, spec)
+                }
+            })
+            .with_reorg_depth(reorg_depth);
         Self {
             indexing_state: RwLock::new(indexing_state),
             block_by_root_requests: RwLock::new(BlockByRootRequests::from_block_requests(
@@ -54,6 +90,9 @@ impl<E: EthSpec + DeserializeOwned> Stores<E> {
             validators_cache: Arc::new(RwLock::new(ModelCache::new(base_dir.clone()))),
             validators_extended_cache: Arc::new(RwLock::new(ModelCache::new(base_dir.clone()))),
             meta_cache: Arc::new(RwLock::new(MetaCache::new(base_dir))),
+            sortable_indexes: SortableIndexes::default(),
+            orphaned_blocks: Mutex::new(OrphanedBlocks::default()),
+            clock,
         }
     }
 }
@@ -99,6 +138,24 @@ impl<E: EthSpec> Stores<E> {
         self.meta_cache.write()
     }
 
+    pub fn pending_sortables_mut(&self) -> MutexGuard<PendingSortables> {
+        self.sortable_indexes.pending_mut()
+    }
+
+    /// Merges the models persisted since the last merge into the sorted indexes
+    pub fn merge_sortable_indexes(&self, base_dir: &str) -> Result<(), String> {
+        self.sortable_indexes.merge(base_dir, &self.meta_cache)
+    }
+
+    /// Locked while an epoch is saved, so that the blocks orphaned meanwhile aren't lost
+    pub fn orphaned_blocks_mut(&self) -> MutexGuard<OrphanedBlocks> {
+        self.orphaned_blocks.lock()
+    }
+
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
     pub fn beacon_state(&self) -> MappedRwLockReadGuard<BeaconState<E>> {
         RwLockReadGuard::map(self.indexing_state(), |indexing_state| {
             &indexing_state.beacon_state
@@ -115,3 +172,20 @@ impl<E: EthSpec> Stores<E> {
         RwLockReadGuard::map(self.indexing_state(), |indexing_state| &indexing_state.spec)
     }
 }
+
+/// The persisted indexing state, telling why it's ignored when it can't be loaded
+fn load_indexing_state<E: EthSpec + DeserializeOwned>(path: &str) -> Option<IndexingState<E>> {
+    if !Path::new(path).exists() {
+        return None;
+    }
+
+    match IndexingState::deserialize_from_file(path) {
+        Ok(indexing_state) => Some(indexing_state),
+        Err(err) => {
+            error!(
+                "Ignoring the indexing state, recovering from the latest snapshot instead: {err}"
+            );
+            None
+        }
+    }
+}
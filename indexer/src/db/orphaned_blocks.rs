@@ -0,0 +1,130 @@
+use std::{collections::HashMap, path::Path};
+
+use types::{
+    epoch::{EpochModel, EpochModelWithId},
+    path::{FromPath, ToPath},
+    persistable::ResolvablePersistable,
+};
+
+/// Counts the orphaned blocks found in epochs which had already been aggregated. The epoch may
+/// still be persisting, in which case its orphaned blocks are kept until it's saved.
+#[derive(Default)]
+pub struct OrphanedBlocks {
+    pending: HashMap<u64, usize>,
+}
+
+impl OrphanedBlocks {
+    /// Moves a missed block of the epoch to the orphaned ones, returning the epoch if it had
+    /// already been persisted
+    pub fn orphan(
+        &mut self,
+        base_dir: &str,
+        epoch: u64,
+    ) -> Result<Option<EpochModelWithId>, String> {
+        if !Path::new(&EpochModel::to_path(base_dir, &epoch)).exists() {
+            *self.pending.entry(epoch).or_default() += 1;
+
+            return Ok(None);
+        }
+
+        let mut model = EpochModel::from_path(base_dir, &epoch)?;
+
+        orphan_missed_blocks(&mut model, 1);
+
+        let epoch_model = EpochModelWithId { id: epoch, model };
+
+        epoch_model.save(base_dir)?;
+
+        Ok(Some(epoch_model))
+    }
+
+    /// Applies the orphaned blocks found while the epoch was persisting, right before it's saved
+    pub fn apply(&mut self, epoch_model: &mut EpochModelWithId) {
+        if let Some(count) = self.pending.remove(&epoch_model.id) {
+            orphan_missed_blocks(&mut epoch_model.model, count);
+        }
+    }
+}
+
+fn orphan_missed_blocks(model: &mut EpochModel, count: usize) {
+    model.missed_blocks_count = model.missed_blocks_count.saturating_sub(count);
+    model.orphaned_blocks_count += count;
+}
+
+#[cfg(test)]
+mod tests {
+    use types::{
+        epoch::{EpochModel, EpochModelWithId},
+        path::{Dirs, FromPath},
+        persistable::ResolvablePersistable,
+    };
+
+    use super::OrphanedBlocks;
+    use crate::test_utils::temp_base_dir;
+
+    fn epoch_model(id: u64, missed_blocks_count: usize) -> EpochModelWithId {
+        EpochModelWithId {
+            id,
+            model: EpochModel {
+                timestamp: 0,
+                proposed_blocks_count: 32 - missed_blocks_count,
+                missed_blocks_count,
+                orphaned_blocks_count: 0,
+                proposer_slashings_count: 0,
+                attester_slashings_count: 0,
+                attestations_count: 0,
+                deposits_count: 0,
+                eligible_ether: 0,
+                voted_ether: 0,
+                withdrawals_count: 0,
+                bls_to_execution_changes_count: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn test_orphan_in_persisted_epoch() {
+        let base_dir = temp_base_dir("orphan_in_persisted_epoch");
+        EpochModel::create_dirs(&base_dir).unwrap();
+        epoch_model(3, 2).save(&base_dir).unwrap();
+
+        let mut orphaned_blocks = OrphanedBlocks::default();
+        let saved = orphaned_blocks.orphan(&base_dir, 3).unwrap().unwrap();
+
+        assert_eq!(saved.model.missed_blocks_count, 1);
+        assert_eq!(saved.model.orphaned_blocks_count, 1);
+
+        let persisted = EpochModel::from_path(&base_dir, &3).unwrap();
+
+        assert_eq!(persisted.missed_blocks_count, 1);
+        assert_eq!(persisted.orphaned_blocks_count, 1);
+    }
+
+    #[test]
+    fn test_orphan_in_persisting_epoch() {
+        let base_dir = temp_base_dir("orphan_in_persisting_epoch");
+        EpochModel::create_dirs(&base_dir).unwrap();
+
+        let mut orphaned_blocks = OrphanedBlocks::default();
+
+        assert!(orphaned_blocks.orphan(&base_dir, 3).unwrap().is_none());
+        assert!(orphaned_blocks.orphan(&base_dir, 3).unwrap().is_none());
+
+        let mut other_epoch = epoch_model(4, 2);
+        orphaned_blocks.apply(&mut other_epoch);
+
+        assert_eq!(other_epoch.model.orphaned_blocks_count, 0);
+
+        let mut epoch = epoch_model(3, 2);
+        orphaned_blocks.apply(&mut epoch);
+
+        assert_eq!(epoch.model.missed_blocks_count, 0);
+        assert_eq!(epoch.model.orphaned_blocks_count, 2);
+
+        // The orphaned blocks are only applied once
+        let mut epoch = epoch_model(3, 2);
+        orphaned_blocks.apply(&mut epoch);
+
+        assert_eq!(epoch.model.orphaned_blocks_count, 0);
+    }
+}
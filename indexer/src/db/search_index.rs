@@ -0,0 +1,32 @@
+use std::collections::BTreeMap;
+
+use types::{
+    path::FromPath,
+    persistable::ResolvablePersistable,
+    search::{shard_id, SearchResult, SearchShardModel, SearchShardModelWithId},
+};
+
+/// Adds search entries to their shards. Each shard is read and rewritten once.
+pub fn insert<I>(base_dir: &str, entries: I) -> Result<(), String>
+where
+    I: IntoIterator<Item = (String, SearchResult)>,
+{
+    let mut shards: BTreeMap<String, Vec<(String, SearchResult)>> = BTreeMap::new();
+
+    entries.into_iter().for_each(|(key, result)| {
+        shards
+            .entry(shard_id(&key))
+            .or_default()
+            .push((key, result))
+    });
+
+    shards.into_iter().try_for_each(|(id, entries)| {
+        let mut shard = SearchShardModel::from_path(base_dir, &id).unwrap_or_default();
+
+        entries.into_iter().for_each(|(key, result)| {
+            shard.entries.entry(key).or_default().insert(result);
+        });
+
+        SearchShardModelWithId { id, model: shard }.save(base_dir)
+    })
+}
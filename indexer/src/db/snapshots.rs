@@ -0,0 +1,175 @@
+use std::fs;
+
+use lighthouse_types::{ChainSpec, EthSpec};
+use serde::Serialize;
+use tracing::warn;
+use types::{
+    block::BlockModel,
+    meta::Meta,
+    persistable::{MsgPackDeserializable, MsgPackSerializable},
+    DeserializeOwned,
+};
+
+use super::{IndexingState, IndexingStateSnapshot};
+
+/// How many snapshots are kept while indexing, unless told otherwise
+pub const DEFAULT_KEPT_SNAPSHOTS_COUNT: usize = 3;
+
+pub fn snapshots_dir(base_dir: &str) -> String {
+    format!("{base_dir}/snapshots")
+}
+
+fn snapshot_path(base_dir: &str, slot: u64) -> String {
+    format!("{}/{slot}.msg", snapshots_dir(base_dir))
+}
+
+/// Slots of the snapshots, from the oldest to the newest
+pub fn list(base_dir: &str) -> Result<Vec<u64>, String> {
+    let dir = snapshots_dir(base_dir);
+
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(vec![]),
+    };
+
+    let mut slots = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter_map(|name| name.strip_suffix(".msg").and_then(|slot| slot.parse().ok()))
+        .collect::<Vec<u64>>();
+
+    slots.sort_unstable();
+
+    Ok(slots)
+}
+
+/// The snapshot is written next to its final path first, so that a crash never leaves a truncated one.
+pub fn save<E: EthSpec + Serialize>(
+    base_dir: &str,
+    indexing_state: &IndexingStateSnapshot<E>,
+) -> Result<(), String> {
+    let slot = match indexing_state.latest_slot() {
+        Some(slot) => slot.as_u64(),
+        None => return Ok(()),
+    };
+
+    let dir = snapshots_dir(base_dir);
+
+    fs::create_dir_all(&dir).map_err(|err| format!("Failed to create '{dir}': {err}"))?;
+
+    let path = snapshot_path(base_dir, slot);
+    let tmp_path = format!("{path}.tmp");
+
+    indexing_state.serialize_to_file(&tmp_path)?;
+
+    fs::rename(&tmp_path, &path).map_err(|err| format!("Can't rename '{tmp_path}': {err}"))
+}
+
+/// Removes all but the `keep` newest snapshots, returning the slots of the removed ones
+pub fn prune(base_dir: &str, keep: usize) -> Result<Vec<u64>, String> {
+    let slots = list(base_dir)?;
+    let removed = slots[..slots.len().saturating_sub(keep)].to_vec();
+
+    removed.iter().try_for_each(|slot| {
+        let path = snapshot_path(base_dir, *slot);
+
+        fs::remove_file(&path).map_err(|err| format!("Failed to remove '{path}': {err}"))
+    })?;
+
+    Ok(removed)
+}
+
+/// The newest snapshot which can be deserialized and isn't ahead of the persisted blocks
+pub fn load_latest<E: EthSpec + DeserializeOwned>(
+    base_dir: &str,
+    spec: &ChainSpec,
+) -> Option<IndexingState<E>> {
+    let persisted_slots_count = Meta::deserialize_from_file(&Meta::to_path::<BlockModel>(base_dir))
+        .map(|meta| meta.count as u64)
+        .unwrap_or(0);
+
+    list(base_dir)
+        .ok()?
+        .into_iter()
+        .rev()
+        .filter(|slot| *slot < persisted_slots_count)
+        .find_map(|slot| {
+            match IndexingState::<E>::deserialize_from_file(&snapshot_path(base_dir, slot)) {
+                Ok(indexing_state) => Some(indexing_state.with_spec(spec.clone())),
+                Err(err) => {
+                    warn!(slot, "Skipping invalid snapshot: {err}");
+                    None
+                }
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, sync::Arc};
+
+    use lighthouse_types::{MainnetEthSpec, Slot};
+    use shared::utils::clock::Clock;
+    use types::{block::BlockModel, meta::Meta, path::Dirs};
+
+    use super::{list, load_latest, prune, save, snapshot_path};
+    use crate::{
+        db::IndexingState,
+        test_utils::{temp_base_dir, BeaconChainHarness},
+        types::block_state::BlockState,
+    };
+
+    #[tokio::test]
+    async fn test_save_prune_and_recover() {
+        let base_dir = temp_base_dir("snapshots");
+        let mut harness = BeaconChainHarness::new();
+        let clock = Clock::new(harness.state().genesis_time(), &harness.spec());
+        let mut indexing_state =
+            IndexingState::<MainnetEthSpec>::new(harness.state(), 0, harness.spec());
+
+        // Nothing to snapshot before the first block
+        save(&base_dir, &indexing_state.snapshot()).unwrap();
+
+        assert!(list(&base_dir).unwrap().is_empty());
+
+        let at_0 = Arc::new(harness.make_block(0).await);
+
+        indexing_state
+            .process_block(BlockState::Proposed(at_0.clone()), &clock)
+            .unwrap();
+        save(&base_dir, &indexing_state.snapshot()).unwrap();
+
+        for slot in 1..4 {
+            indexing_state
+                .process_block(BlockState::Missed(Slot::new(slot)), &clock)
+                .unwrap();
+            save(&base_dir, &indexing_state.snapshot()).unwrap();
+        }
+
+        assert_eq!(list(&base_dir).unwrap(), vec![0, 1, 2, 3]);
+        assert_eq!(prune(&base_dir, 3).unwrap(), vec![0]);
+        assert_eq!(list(&base_dir).unwrap(), vec![1, 2, 3]);
+
+        // Every snapshot is ahead of the persisted blocks
+        assert!(load_latest::<MainnetEthSpec>(&base_dir, &harness.spec()).is_none());
+
+        BlockModel::create_dirs(&base_dir).unwrap();
+        Meta {
+            count: 3,
+            ..Default::default()
+        }
+        .save::<BlockModel>(&base_dir)
+        .unwrap();
+
+        fs::write(snapshot_path(&base_dir, 2), b"truncated").unwrap();
+
+        // The snapshot at 3 is ahead of the persisted blocks and the one at 2 is invalid
+        let indexing_state = load_latest::<MainnetEthSpec>(&base_dir, &harness.spec()).unwrap();
+
+        assert_eq!(indexing_state.latest_slot(), Some(Slot::new(1)));
+        assert_eq!(
+            indexing_state.latest_block_root(),
+            Some(at_0.canonical_root())
+        );
+    }
+}
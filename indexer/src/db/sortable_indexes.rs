@@ -0,0 +1,112 @@
+use parking_lot::{Mutex, MutexGuard, RwLock};
+use types::{
+    block::{BlockModel, PendingSortablesBlockModel},
+    block_request::{BlockRequestModel, PendingSortablesBlockRequestModel},
+    epoch::{EpochModel, PendingSortablesEpochModel},
+    execution_payload::{ExecutionPayloadModel, PendingSortablesExecutionPayloadModel},
+    good_peer::{GoodPeerModel, PendingSortablesGoodPeerModel},
+    meta::Meta,
+    path::Prefix,
+    slashing::{PendingSortablesSlashingModel, SlashingModel},
+    utils::{MetaCache, PendingSortable},
+};
+
+/// The orderables of the models persisted since the sorted indexes were last merged, by model
+#[derive(Default)]
+pub struct PendingSortables {
+    pub epochs: PendingSortablesEpochModel,
+    pub blocks: PendingSortablesBlockModel,
+    pub execution_payloads: PendingSortablesExecutionPayloadModel,
+    pub slashings: PendingSortablesSlashingModel,
+    pub block_requests: PendingSortablesBlockRequestModel,
+    pub good_peers: PendingSortablesGoodPeerModel,
+}
+
+/// Keeps the sorted indexes up to date while indexing. The persist workers register the models
+/// they write, which are periodically merged into the runs the indexes are derived from.
+#[derive(Default)]
+pub struct SortableIndexes {
+    pending: Mutex<PendingSortables>,
+    merging: Mutex<()>,
+}
+
+impl SortableIndexes {
+    pub fn pending_mut(&self) -> MutexGuard<PendingSortables> {
+        self.pending.lock()
+    }
+
+    /// Merges the registered models into the sorted indexes. The models registered meanwhile
+    /// are merged next time, and only one merge runs at a time. Each model is merged on its
+    /// own, the ones which failed being registered back.
+    pub fn merge(&self, base_dir: &str, meta_cache: &RwLock<MetaCache>) -> Result<(), String> {
+        let _merging = self.merging.lock();
+        let pending = std::mem::take(&mut *self.pending.lock());
+
+        let errors = vec![
+            self.merge_model::<EpochModel, _>(base_dir, meta_cache, pending.epochs, |p| {
+                &mut p.epochs
+            }),
+            self.merge_model::<BlockModel, _>(base_dir, meta_cache, pending.blocks, |p| {
+                &mut p.blocks
+            }),
+            self.merge_model::<ExecutionPayloadModel, _>(
+                base_dir,
+                meta_cache,
+                pending.execution_payloads,
+                |p| &mut p.execution_payloads,
+            ),
+            self.merge_model::<SlashingModel, _>(base_dir, meta_cache, pending.slashings, |p| {
+                &mut p.slashings
+            }),
+            self.merge_model::<BlockRequestModel, _>(
+                base_dir,
+                meta_cache,
+                pending.block_requests,
+                |p| &mut p.block_requests,
+            ),
+            self.merge_model::<GoodPeerModel, _>(base_dir, meta_cache, pending.good_peers, |p| {
+                &mut p.good_peers
+            }),
+        ]
+        .into_iter()
+        .filter_map(Result::err)
+        .collect::<Vec<_>>();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join(", "))
+        }
+    }
+
+    /// Merges the orderables of a model, recording the lengths of its sorted indexes in its meta.
+    /// The orderables are registered back when the merge fails, so that they're merged next time.
+    fn merge_model<M: Prefix, P: PendingSortable>(
+        &self,
+        base_dir: &str,
+        meta_cache: &RwLock<MetaCache>,
+        pending: P,
+        registered: impl FnOnce(&mut PendingSortables) -> &mut P,
+    ) -> Result<(), String> {
+        match pending.merge(base_dir, &meta::<M>(meta_cache)) {
+            Ok(lengths) if lengths.is_empty() => Ok(()),
+            Ok(lengths) => {
+                let mut meta_cache = meta_cache.write();
+                let meta = meta_cache.loaded_entry::<M>().or_insert_with(Meta::default);
+
+                meta.sortable_lengths.extend(lengths);
+                meta.save::<M>(base_dir)
+                    .map_err(|err| format!("{}: {err}", M::prefix()))
+            }
+            Err(err) => {
+                pending.requeue(registered(&mut self.pending.lock()));
+                Err(format!("{}: {err}", M::prefix()))
+            }
+        }
+    }
+}
+
+/// A copy of the meta of the model, so that the cache isn't locked during the merge
+fn meta<M: Prefix>(meta_cache: &RwLock<MetaCache>) -> Meta {
+    meta_cache.write().get::<M>().cloned().unwrap_or_default()
+}
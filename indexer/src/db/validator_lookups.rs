@@ -0,0 +1,68 @@
+use types::{
+    path::FromPath,
+    persistable::ResolvablePersistable,
+    validator::ValidatorModel,
+    validator_lookup::{
+        shard_id, withdrawal_address, PubkeyShardModel, PubkeyShardModelWithId,
+        WithdrawalAddressShardModel, WithdrawalAddressShardModelWithId,
+    },
+};
+
+/// Updates the public key and withdrawal address lookups of a validator which is about to be
+/// replaced by `model`. Only the shards whose entry changes are rewritten.
+pub fn update(
+    base_dir: &str,
+    validator_index: u64,
+    previous: Option<&ValidatorModel>,
+    model: &ValidatorModel,
+) -> Result<(), String> {
+    if previous.map(|previous| &previous.pubkey) != Some(&model.pubkey) {
+        let id = shard_id(&model.pubkey);
+        let mut shard = PubkeyShardModel::from_path(base_dir, &id).unwrap_or_default();
+
+        shard
+            .validators
+            .insert(model.pubkey.clone(), validator_index);
+
+        PubkeyShardModelWithId { id, model: shard }.save(base_dir)?;
+    }
+
+    let previous_address =
+        previous.and_then(|previous| withdrawal_address(&previous.withdrawal_credentials));
+    let address = withdrawal_address(&model.withdrawal_credentials);
+
+    if previous_address == address {
+        return Ok(());
+    }
+
+    if let Some(previous_address) = previous_address {
+        update_withdrawal_address(base_dir, &previous_address, |validators| {
+            validators.remove(&validator_index);
+        })?;
+    }
+
+    if let Some(address) = address {
+        update_withdrawal_address(base_dir, &address, |validators| {
+            validators.insert(validator_index);
+        })?;
+    }
+
+    Ok(())
+}
+
+fn update_withdrawal_address<F>(base_dir: &str, address: &str, f: F) -> Result<(), String>
+where
+    F: FnOnce(&mut std::collections::BTreeSet<u64>),
+{
+    let id = shard_id(address);
+    let mut shard = WithdrawalAddressShardModel::from_path(base_dir, &id).unwrap_or_default();
+    let validators = shard.validators.entry(address.to_string()).or_default();
+
+    f(validators);
+
+    if validators.is_empty() {
+        shard.validators.remove(address);
+    }
+
+    WithdrawalAddressShardModelWithId { id, model: shard }.save(base_dir)
+}
@@ -11,10 +11,16 @@ use types::{
 };
 
 use crate::{
-    beacon_chain::beacon_context::BeaconContext,
+    beacon_chain::{beacon_context::BeaconContext, checkpoint::Checkpoint},
     db::Stores,
-    network::{spawn_consensus_network, spawn_execution_network, ExecutionNetworkCommand},
-    workers::{spawn_index_worker, spawn_persist_block_worker, spawn_persist_validator_worker},
+    network::{
+        spawn_beacon_api_network, spawn_consensus_network, spawn_execution_network,
+        ExecutionNetworkCommand, Source,
+    },
+    workers::{
+        spawn_backfill_worker, spawn_index_worker, spawn_persist_block_worker,
+        spawn_persist_validator_worker,
+    },
 };
 
 mod works;
@@ -23,34 +29,51 @@ mod works;
 pub struct Indexer;
 
 impl Indexer {
+    #[allow(clippy::too_many_arguments)]
     pub fn spawn_services<E: EthSpec + Serialize + DeserializeOwned>(
         self,
         dry: bool,
+        backfill: bool,
+        source: Source,
+        kept_snapshots: usize,
+        reorg_depth: usize,
         base_dir: String,
         execution_node_url: String,
         executor: TaskExecutor,
         beacon_context: Arc<BeaconContext<E>>,
+        checkpoint: Option<Checkpoint<E>>,
         shutdown_handle: Sender<()>,
         shutdown_trigger: Receiver<()>,
     ) {
         self.spawn_indexer(
             executor,
             dry,
+            backfill,
+            source,
+            kept_snapshots,
+            reorg_depth,
             base_dir,
             execution_node_url,
             beacon_context,
+            checkpoint,
             shutdown_handle,
             shutdown_trigger,
         );
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn spawn_indexer<E: EthSpec + Serialize + DeserializeOwned>(
         &self,
         executor: TaskExecutor,
         dry: bool,
+        backfill: bool,
+        source: Source,
+        kept_snapshots: usize,
+        reorg_depth: usize,
         base_dir: String,
         execution_node_url: String,
         beacon_context: Arc<BeaconContext<E>>,
+        checkpoint: Option<Checkpoint<E>>,
         shutdown_handle: Sender<()>,
         mut shutdown_trigger: Receiver<()>,
     ) {
@@ -65,18 +88,38 @@ impl Indexer {
                     .collect::<HashMap<PeerId, Multiaddr>>();
 
                 let block_requests = BlockRequestModelWithId::iter(&base_dir).unwrap();
-                let stores = Arc::new(Stores::new(base_dir.clone(), beacon_context.genesis_state.clone(), beacon_context.eth2_network_config.deposit_contract_deploy_block, block_requests.collect()));
+                let stores = Arc::new(Stores::new(base_dir.clone(), beacon_context.genesis_state.clone(), beacon_context.eth2_network_config.deposit_contract_deploy_block, block_requests.collect(), checkpoint.as_ref(), reorg_depth, beacon_context.spec.clone()));
 
                 let (execution_command_send, execution_event_recv) = spawn_execution_network(
                 execution_node_url.parse().unwrap(), beacon_context.clone(), &executor)
                         .unwrap();
 
-                let (consensus_command_send, consensus_event_recv) = spawn_consensus_network(beacon_context.clone(), good_peers, &executor)
-                    .await
-                    .unwrap();
+                let (consensus_command_send, consensus_event_recv, backfill_event_recv) = match source {
+                    Source::P2p => spawn_consensus_network(beacon_context.clone(), good_peers, &executor).await,
+                    Source::BeaconApi { beacon_node_url } => spawn_beacon_api_network(beacon_node_url, beacon_context.clone(), &executor),
+                }
+                .unwrap();
 
                 let new_block_send = spawn_persist_block_worker(base_dir.clone(), stores.clone(), shutdown_trigger.clone(), &executor);
 
+                let anchor_block = checkpoint.map(|checkpoint| checkpoint.block);
+
+                if let Some(anchor_block) = &anchor_block {
+                    works::persist_anchor_block(&base_dir, anchor_block.clone(), &stores, &new_block_send);
+                }
+
+                if backfill {
+                    spawn_backfill_worker(
+                        base_dir.clone(),
+                        beacon_context.clone(),
+                        anchor_block,
+                        backfill_event_recv,
+                        consensus_command_send.clone(),
+                        stores.clone(),
+                        shutdown_trigger.clone(),
+                        &executor);
+                }
+
                 let validator_event_send = spawn_persist_validator_worker(base_dir.clone(), stores.clone(), &executor, shutdown_handle);
 
                 let latest_deposit_block = stores.indexing_state().latest_deposit_block();
@@ -95,11 +138,12 @@ impl Indexer {
                     tokio::select! {
                         work = work_recv.recv(), if !dry => {
                             match work {
-                                Some(work) => works::handle(base_dir.clone(), work, &stores, &new_block_send, &validator_event_send, &executor),
+                                Some(work) => works::handle(base_dir.clone(), work, &stores, &new_block_send, &validator_event_send, kept_snapshots, &executor),
                                 None => {
                                     works::persist_indexing_state(&base_dir, &stores);
                                     works::persist_block_requests(&base_dir, &stores);
                                     works::persist_good_peers(&base_dir, &stores);
+                                    works::persist_sortable_indexes(&base_dir, &stores);
                                     works::persist_validators(&base_dir, &stores);
                                     return
                                 },
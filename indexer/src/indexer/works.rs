@@ -1,21 +1,25 @@
 use std::sync::Arc;
 
-use lighthouse_types::EthSpec;
+use lighthouse_types::{Epoch, EthSpec, SignedBeaconBlock};
 use serde::Serialize;
 use task_executor::TaskExecutor;
 use tokio::sync::mpsc::UnboundedSender;
 
-use tracing::info;
+use tracing::{error, info};
 use types::{
+    block::BlockModel,
     block_request::{BlockRequestModel, BlockRequestModelWithId},
+    block_root::{BlockRootModel, BlockRootModelWithId},
     deposit::ExecutionLayerDepositModel,
+    epoch::EpochModel,
     good_peer::{GoodPeerModel, GoodPeerModelWithId},
+    meta::Meta,
     persistable::ResolvablePersistable,
     validator::{ValidatorExtendedModel, ValidatorModel},
 };
 
 use crate::{
-    db::Stores,
+    db::{snapshots, Stores},
     types::consolidated_block::ConsolidatedBlock,
     work::Work,
     workers::{spawn_persist_epoch_worker, ValidatorEvent},
@@ -27,6 +31,7 @@ pub fn handle<E: EthSpec + Serialize>(
     stores: &Arc<Stores<E>>,
     new_block_send: &UnboundedSender<ConsolidatedBlock<E>>,
     validator_event_send: &UnboundedSender<ValidatorEvent>,
+    kept_snapshots: usize,
     executor: &TaskExecutor,
 ) {
     match work {
@@ -34,6 +39,8 @@ pub fn handle<E: EthSpec + Serialize>(
             persist_indexing_state(&base_dir, stores);
         }
 
+        Work::PersistSnapshot => persist_snapshot(base_dir, stores, kept_snapshots, executor),
+
         Work::PersistDepositFromExecutionLayer(deposit) => validator_event_send
             .send(ValidatorEvent::NewDepositFromExecutionLayer(deposit))
             .unwrap(),
@@ -42,10 +49,16 @@ pub fn handle<E: EthSpec + Serialize>(
 
         Work::PersistEpoch(epoch) => spawn_persist_epoch_worker(base_dir, epoch, stores, executor),
 
+        Work::PersistOrphanInEpoch(epoch) => persist_orphan_in_epoch(&base_dir, epoch, stores),
+
         Work::PersistBlockRequest(root, attempts) => {
             let block_request = BlockRequestModelWithId::from((&root, &attempts));
 
             block_request.save(&base_dir).unwrap();
+            stores
+                .pending_sortables_mut()
+                .block_requests
+                .push(&block_request);
         }
 
         Work::PersistAllBlockRequests => persist_block_requests(&base_dir, stores),
@@ -54,16 +67,115 @@ pub fn handle<E: EthSpec + Serialize>(
     }
 }
 
+/// Moves a missed block of an already aggregated epoch to the orphaned ones. If the epoch is still
+/// persisting, the block is counted once it's saved.
+fn persist_orphan_in_epoch<E: EthSpec>(base_dir: &str, epoch: Epoch, stores: &Arc<Stores<E>>) {
+    let result = stores
+        .orphaned_blocks_mut()
+        .orphan(base_dir, epoch.as_u64());
+
+    match result {
+        Ok(Some(epoch_model)) => stores.pending_sortables_mut().epochs.push(&epoch_model),
+        Ok(None) => {}
+        Err(err) => error!(%epoch, "Unable to persist the orphaned block count: {err}"),
+    }
+}
+
 pub fn persist_indexing_state<E: EthSpec + Serialize>(base_dir: &str, stores: &Arc<Stores<E>>) {
     info!("Persisting indexing state");
     stores.indexing_state().save(base_dir).unwrap();
 }
 
+/// Snapshots the indexing state and keeps only the most recent snapshots. The state is only
+/// locked while it's copied, it's serialized in the background.
+fn persist_snapshot<E: EthSpec + Serialize>(
+    base_dir: String,
+    stores: &Arc<Stores<E>>,
+    kept_snapshots: usize,
+    executor: &TaskExecutor,
+) {
+    let snapshot = stores.indexing_state().snapshot();
+
+    executor.spawn_blocking(
+        move || {
+            let result = snapshots::save(&base_dir, &snapshot)
+                .and_then(|_| snapshots::prune(&base_dir, kept_snapshots));
+
+            if let Err(err) = result {
+                error!("Unable to persist the indexing state snapshot: {err}");
+            }
+        },
+        "persist snapshot",
+    );
+}
+
+/// Persists the block the indexing started from when it started from a checkpoint,
+/// and flags everything before it as not indexed.
+pub fn persist_anchor_block<E: EthSpec>(
+    base_dir: &str,
+    block: Arc<SignedBeaconBlock<E>>,
+    stores: &Arc<Stores<E>>,
+    new_block_send: &UnboundedSender<ConsolidatedBlock<E>>,
+) {
+    let slot = block.slot();
+
+    if stores.indexing_state().anchor_slot() != Some(slot)
+        || stores.meta_cache_mut().get::<BlockModel>().is_some()
+    {
+        // Either the checkpoint isn't the one the indexing started from or it has already been persisted
+        return;
+    }
+
+    info!(%slot, "Persisting checkpoint block");
+
+    let epoch = slot.epoch(E::slots_per_epoch());
+    let mut meta_cache = stores.meta_cache_mut();
+
+    meta_cache.insert::<BlockModel>(Meta::partial(slot.as_usize(), 0..slot.as_u64()));
+    meta_cache.insert::<EpochModel>(Meta::partial(epoch.as_usize(), 0..epoch.as_u64()));
+
+    meta_cache
+        .get::<BlockModel>()
+        .expect("The block meta has just been inserted")
+        .save::<BlockModel>(base_dir)
+        .unwrap();
+    meta_cache
+        .get::<EpochModel>()
+        .expect("The epoch meta has just been inserted")
+        .save::<EpochModel>(base_dir)
+        .unwrap();
+
+    drop(meta_cache);
+
+    stores.block_roots_cache().write().put(BlockRootModelWithId {
+        id: format!("{:?}", block.canonical_root()),
+        model: BlockRootModel {
+            slot: slot.as_u64(),
+        },
+    });
+
+    let anchor_block = stores
+        .indexing_state_mut()
+        .process_anchor_block(block, stores.clock());
+
+    match anchor_block {
+        Ok(block) => new_block_send.send(block).unwrap(),
+        Err(err) => error!("Unable to process the checkpoint block: {err}"),
+    }
+}
+
 pub fn persist_block_requests<E: EthSpec>(base_dir: &str, stores: &Arc<Stores<E>>) {
     let block_requests = Vec::<BlockRequestModelWithId>::from(&*stores.block_by_root_requests());
 
     block_requests.save(base_dir).unwrap();
 
+    block_requests.iter().for_each(|block_request| {
+        stores
+            .pending_sortables_mut()
+            .block_requests
+            .push(block_request)
+    });
+
     stores
         .meta_cache_mut()
         .entry::<BlockRequestModel>()
@@ -77,6 +189,10 @@ pub fn persist_good_peers<E: EthSpec>(base_dir: &str, stores: &Arc<Stores<E>>) {
 
     good_peers.save(base_dir).unwrap();
 
+    good_peers
+        .iter()
+        .for_each(|good_peer| stores.pending_sortables_mut().good_peers.push(good_peer));
+
     stores
         .meta_cache_mut()
         .entry::<GoodPeerModel>()
@@ -85,6 +201,13 @@ pub fn persist_good_peers<E: EthSpec>(base_dir: &str, stores: &Arc<Stores<E>>) {
         .unwrap();
 }
 
+/// Merges the models persisted since the last epoch into the sorted indexes
+pub fn persist_sortable_indexes<E: EthSpec>(base_dir: &str, stores: &Arc<Stores<E>>) {
+    if let Err(err) = stores.merge_sortable_indexes(base_dir) {
+        error!("Unable to merge the sorted indexes: {err}");
+    }
+}
+
 pub fn persist_validators<E: EthSpec>(base_dir: &str, stores: &Arc<Stores<E>>) {
     let beacon_state = stores.beacon_state();
     let el_deposits_count = beacon_state.eth1_deposit_index() as usize;
@@ -1,42 +1,156 @@
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 
 use environment::EnvironmentBuilder;
-use lighthouse_types::MainnetEthSpec;
+use eth2_network_config::Eth2NetworkConfig;
+use lighthouse_types::{EthSpec, EthSpecId};
+use serde::Serialize;
 
 use tokio::{
     signal,
     sync::{mpsc, watch},
 };
-use tracing::warn;
+use tracing::{info, warn};
 use types::{
     attestation::AttestationModel,
+    attestation_performance::AttestationPerformanceModel,
     block::{BlockExtendedModel, BlockModel, BlockModelWithId, PersistIteratorBlockModel},
     block_request::{BlockRequestModel, BlockRequestModelWithId, PersistIteratorBlockRequestModel},
     block_root::BlockRootModel,
     committee::CommitteeModel,
     deposit::{ConsensusLayerDepositModel, ExecutionLayerDepositModel},
     epoch::{EpochExtendedModel, EpochModel, EpochModelWithId, PersistIteratorEpochModel},
+    execution_payload::{
+        ExecutionBlockNumberModel, ExecutionPayloadModel, ExecutionPayloadModelWithId,
+        PersistIteratorExecutionPayloadModel,
+    },
     good_peer::{GoodPeerModel, GoodPeerModelWithId, PersistIteratorGoodPeerModel},
-    path::Dirs,
+    graffiti::{GraffitiShardModel, GraffitiTallyModel},
+    path::{Dirs, ToPath},
+    search::SearchShardModel,
+    slashing::{
+        AttesterSlashingModel, PersistIteratorSlashingModel, ProposerSlashingModel, SlashingModel,
+        SlashingModelWithId,
+    },
+    sync_committee::{SyncAggregateModel, SyncCommitteeModel},
     validator::{ValidatorExtendedModel, ValidatorModel},
+    validator_balances::ValidatorBalancesModel,
+    validator_lookup::{PubkeyShardModel, WithdrawalAddressShardModel},
+    voluntary_exit::{IndexedVoluntaryExitModel, VoluntaryExitModel},
     vote::VoteModel,
+    withdrawal::{BlsToExecutionChangeModel, WithdrawalModel},
+    DeserializeOwned,
 };
 
+pub use crate::{
+    db::{snapshots::DEFAULT_KEPT_SNAPSHOTS_COUNT, DEFAULT_REORG_DEPTH},
+    network::Source,
+};
+pub use types::meta::DEFAULT_SORTABLE_CHUNK_SIZE;
+
 use crate::{
-    beacon_chain::beacon_context::{build_environment, BeaconContext},
+    beacon_chain::{
+        beacon_context::{build_environment, load_eth2_network_config, BeaconContext},
+        checkpoint::Checkpoint,
+    },
+    beacon_node_client::BeaconNodeClient,
+    db::{snapshots, IndexingState},
     indexer::Indexer,
 };
 
+/// Where the indexing starts from when it doesn't start from genesis
+pub enum CheckpointSource {
+    /// The SSZ encoded state and block files
+    Files(String, String),
+    /// The latest finalized block of the beacon node at this url
+    BeaconNode(String),
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn start_indexer(
     reset: bool,
     dry: bool,
+    backfill: bool,
+    source: Source,
+    kept_snapshots: usize,
+    reorg_depth: usize,
+    base_dir: String,
+    execution_node_url: String,
+    network: String,
+    checkpoint: Option<CheckpointSource>,
+) -> Result<(), String> {
+    let eth2_network_config = load_eth2_network_config(&network)?;
+
+    match eth2_network_config.eth_spec_id()? {
+        EthSpecId::Mainnet => run_indexer(
+            EnvironmentBuilder::mainnet(),
+            eth2_network_config,
+            reset,
+            dry,
+            backfill,
+            source,
+            kept_snapshots,
+            reorg_depth,
+            base_dir,
+            execution_node_url,
+            checkpoint,
+        ),
+        EthSpecId::Minimal => run_indexer(
+            EnvironmentBuilder::minimal(),
+            eth2_network_config,
+            reset,
+            dry,
+            backfill,
+            source,
+            kept_snapshots,
+            reorg_depth,
+            base_dir,
+            execution_node_url,
+            checkpoint,
+        ),
+        eth_spec_id => Err(format!("The {eth_spec_id:?} spec is not supported")),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_indexer<E: EthSpec + Serialize + DeserializeOwned>(
+    environment_builder: EnvironmentBuilder<E>,
+    eth2_network_config: Eth2NetworkConfig,
+    reset: bool,
+    dry: bool,
+    backfill: bool,
+    source: Source,
+    kept_snapshots: usize,
+    reorg_depth: usize,
     base_dir: String,
     execution_node_url: String,
+    checkpoint: Option<CheckpointSource>,
 ) -> Result<(), String> {
-    let (environment, _) = build_environment(EnvironmentBuilder::mainnet())?;
+    let environment = build_environment(environment_builder, eth2_network_config.clone())?;
     let context = environment.core_context();
-    let beacon_context = BeaconContext::<MainnetEthSpec>::build(context.eth2_config.spec)?;
+    let beacon_context = BeaconContext::<E>::build(eth2_network_config, context.eth2_config.spec)?;
     let executor = context.executor;
+    let checkpoint = match checkpoint {
+        Some(CheckpointSource::Files(state_path, block_path)) => Some(Checkpoint::<E>::load(
+            &state_path,
+            &block_path,
+            &beacon_context.spec,
+        )?),
+        Some(CheckpointSource::BeaconNode(_))
+            if !reset && Path::new(&IndexingState::<E>::to_path(&base_dir, &())).exists() =>
+        {
+            info!("Resuming the indexing, the beacon node checkpoint isn't needed");
+            None
+        }
+        Some(CheckpointSource::BeaconNode(beacon_node_url)) => {
+            info!("Downloading the finalized checkpoint of the beacon node");
+
+            Some(environment.runtime().block_on(Checkpoint::<E>::fetch(
+                &BeaconNodeClient::new(beacon_node_url),
+                &beacon_context.spec,
+            ))?)
+        }
+        None => None,
+    };
 
     if reset {
         if let Err(err) = remove_dirs(&base_dir) {
@@ -53,10 +167,15 @@ pub fn start_indexer(
 
         indexer.spawn_services(
             dry,
+            backfill,
+            source,
+            kept_snapshots,
+            reorg_depth,
             base_dir,
             execution_node_url,
             executor,
             Arc::new(beacon_context),
+            checkpoint,
             shutdown_handle,
             shutdown_trigger,
         );
@@ -73,11 +192,34 @@ pub fn start_indexer(
     Ok(())
 }
 
-pub fn update_indexes(base_dir: String) -> Result<(), String> {
-    EpochModelWithId::iter(&base_dir)?.persist_sortables(&base_dir)?;
-    BlockModelWithId::iter(&base_dir)?.persist_sortables(&base_dir)?;
-    BlockRequestModelWithId::iter(&base_dir)?.persist_sortables(&base_dir)?;
-    GoodPeerModelWithId::iter(&base_dir)?.persist_sortables(&base_dir)?;
+/// Rebuilds the sorted indexes and the runs the indexer merges new models into
+pub fn update_indexes(base_dir: String, chunk_size: usize) -> Result<(), String> {
+    if chunk_size == 0 {
+        return Err("The chunk size must be at least 1".to_string());
+    }
+
+    EpochModelWithId::iter(&base_dir)?.persist_sortables(&base_dir, chunk_size)?;
+    BlockModelWithId::iter(&base_dir)?.persist_sortables(&base_dir, chunk_size)?;
+    ExecutionPayloadModelWithId::iter(&base_dir)?.persist_sortables(&base_dir, chunk_size)?;
+    SlashingModelWithId::iter(&base_dir)?.persist_sortables(&base_dir, chunk_size)?;
+    BlockRequestModelWithId::iter(&base_dir)?.persist_sortables(&base_dir, chunk_size)?;
+    GoodPeerModelWithId::iter(&base_dir)?.persist_sortables(&base_dir, chunk_size)?;
+
+    Ok(())
+}
+
+pub fn list_snapshots(base_dir: String) -> Result<(), String> {
+    for slot in snapshots::list(&base_dir)? {
+        println!("{slot}");
+    }
+
+    Ok(())
+}
+
+pub fn prune_snapshots(base_dir: String, keep: usize) -> Result<(), String> {
+    for slot in snapshots::prune(&base_dir, keep)? {
+        println!("Removed snapshot {slot}");
+    }
 
     Ok(())
 }
@@ -89,14 +231,32 @@ fn create_dirs(base_dir: &str) -> Result<(), String> {
     BlockExtendedModel::create_dirs(base_dir)?;
     BlockRootModel::create_dirs(base_dir)?;
     AttestationModel::create_dirs(base_dir)?;
+    AttestationPerformanceModel::create_dirs(base_dir)?;
     CommitteeModel::create_dirs(base_dir)?;
     VoteModel::create_dirs(base_dir)?;
     ValidatorModel::create_dirs(base_dir)?;
     ValidatorExtendedModel::create_dirs(base_dir)?;
+    ValidatorBalancesModel::create_dirs(base_dir)?;
+    PubkeyShardModel::create_dirs(base_dir)?;
+    WithdrawalAddressShardModel::create_dirs(base_dir)?;
     ExecutionLayerDepositModel::create_dirs(base_dir)?;
     ConsensusLayerDepositModel::create_dirs(base_dir)?;
     BlockRequestModel::create_dirs(base_dir)?;
     GoodPeerModel::create_dirs(base_dir)?;
+    SyncCommitteeModel::create_dirs(base_dir)?;
+    SyncAggregateModel::create_dirs(base_dir)?;
+    ExecutionPayloadModel::create_dirs(base_dir)?;
+    ExecutionBlockNumberModel::create_dirs(base_dir)?;
+    WithdrawalModel::create_dirs(base_dir)?;
+    BlsToExecutionChangeModel::create_dirs(base_dir)?;
+    VoluntaryExitModel::create_dirs(base_dir)?;
+    IndexedVoluntaryExitModel::create_dirs(base_dir)?;
+    ProposerSlashingModel::create_dirs(base_dir)?;
+    AttesterSlashingModel::create_dirs(base_dir)?;
+    SlashingModel::create_dirs(base_dir)?;
+    SearchShardModel::create_dirs(base_dir)?;
+    GraffitiShardModel::create_dirs(base_dir)?;
+    GraffitiTallyModel::create_dirs(base_dir)?;
 
     Ok(())
 }
@@ -108,12 +268,31 @@ fn remove_dirs(base_dir: &str) -> Result<(), String> {
     BlockExtendedModel::remove_dirs(base_dir)?;
     BlockRootModel::remove_dirs(base_dir)?;
     AttestationModel::remove_dirs(base_dir)?;
+    AttestationPerformanceModel::remove_dirs(base_dir)?;
     CommitteeModel::remove_dirs(base_dir)?;
     VoteModel::remove_dirs(base_dir)?;
     ValidatorModel::remove_dirs(base_dir)?;
     ValidatorExtendedModel::remove_dirs(base_dir)?;
+    ValidatorBalancesModel::remove_dirs(base_dir)?;
+    PubkeyShardModel::remove_dirs(base_dir)?;
+    WithdrawalAddressShardModel::remove_dirs(base_dir)?;
     ConsensusLayerDepositModel::remove_dirs(base_dir)?;
     BlockRequestModel::remove_dirs(base_dir)?;
+    SyncCommitteeModel::remove_dirs(base_dir)?;
+    SyncAggregateModel::remove_dirs(base_dir)?;
+    ExecutionPayloadModel::remove_dirs(base_dir)?;
+    ExecutionBlockNumberModel::remove_dirs(base_dir)?;
+    WithdrawalModel::remove_dirs(base_dir)?;
+    BlsToExecutionChangeModel::remove_dirs(base_dir)?;
+    VoluntaryExitModel::remove_dirs(base_dir)?;
+    IndexedVoluntaryExitModel::remove_dirs(base_dir)?;
+    ProposerSlashingModel::remove_dirs(base_dir)?;
+    AttesterSlashingModel::remove_dirs(base_dir)?;
+    SlashingModel::remove_dirs(base_dir)?;
+    SearchShardModel::remove_dirs(base_dir)?;
+    GraffitiShardModel::remove_dirs(base_dir)?;
+    GraffitiTallyModel::remove_dirs(base_dir)?;
+    snapshots::prune(base_dir, 0)?;
 
     Ok(())
 }
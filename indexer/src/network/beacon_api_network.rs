@@ -0,0 +1,278 @@
+use std::{sync::Arc, time::Duration};
+
+use eth2::types::BlockId;
+use futures::{stream, StreamExt};
+use lighthouse_network::{MessageId, NetworkEvent, PeerId, PubsubMessage, Response, TopicHash};
+use store::{EthSpec, Hash256, SignedBeaconBlock, Slot};
+use task_executor::TaskExecutor;
+use tokio::{
+    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    time::{interval, sleep, Interval},
+};
+use tracing::{debug, info, warn};
+
+use crate::{beacon_chain::beacon_context::BeaconContext, beacon_node_client::BeaconNodeClient};
+
+use super::consensus_network::{NetworkCommand, RequestId};
+
+/// Blocks fetched for a range request, as a peer would serve them
+const RANGE_REQUEST_COUNT: u64 = 32;
+
+/// Blocks of a range request fetched at the same time
+const CONCURRENT_BLOCK_REQUESTS: usize = 16;
+
+/// Delay before reporting a failed request, so that an unavailable node isn't hammered
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Serves the consensus network commands from a beacon node REST API instead of libp2p.
+/// The beacon node is seen as a single peer, and polling its head stands for the block gossip.
+struct BeaconApiNetwork<E: EthSpec> {
+    client: BeaconNodeClient,
+    peer_id: PeerId,
+    command_recv: UnboundedReceiver<NetworkCommand>,
+    event_send: UnboundedSender<NetworkEvent<RequestId, E>>,
+    backfill_event_send: UnboundedSender<NetworkEvent<RequestId, E>>,
+    head_poll: Option<Interval>,
+    head_root: Option<Hash256>,
+    seconds_per_slot: u64,
+}
+
+pub fn spawn<E: EthSpec>(
+    beacon_node_url: String,
+    beacon_context: Arc<BeaconContext<E>>,
+    executor: &TaskExecutor,
+) -> Result<
+    (
+        UnboundedSender<NetworkCommand>,
+        UnboundedReceiver<NetworkEvent<RequestId, E>>,
+        UnboundedReceiver<NetworkEvent<RequestId, E>>,
+    ),
+    String,
+> {
+    let (command_send, command_recv) = mpsc::unbounded_channel::<NetworkCommand>();
+    let (event_send, event_recv) = mpsc::unbounded_channel::<NetworkEvent<RequestId, E>>();
+    let (backfill_event_send, backfill_event_recv) =
+        mpsc::unbounded_channel::<NetworkEvent<RequestId, E>>();
+
+    let network_service = BeaconApiNetwork {
+        client: BeaconNodeClient::new(beacon_node_url),
+        peer_id: PeerId::random(),
+        command_recv,
+        event_send,
+        backfill_event_send,
+        head_poll: None,
+        head_root: None,
+        seconds_per_slot: beacon_context.spec.seconds_per_slot,
+    };
+
+    network_service.spawn(executor);
+
+    Ok((command_send, event_recv, backfill_event_recv))
+}
+
+impl<E: EthSpec> BeaconApiNetwork<E> {
+    pub fn spawn(mut self, executor: &TaskExecutor) {
+        executor.spawn(
+            async move {
+                info!(peer = %self.peer_id, "Using the beacon node API as the block source");
+
+                // The backfill worker may not be running, so its events can be dropped
+                let _ = self
+                    .backfill_event_send
+                    .send(NetworkEvent::PeerConnectedOutgoing(self.peer_id));
+                if self
+                    .event_send
+                    .send(NetworkEvent::PeerConnectedOutgoing(self.peer_id))
+                    .is_err()
+                {
+                    return;
+                }
+
+                loop {
+                    tokio::select! {
+                        command = self.command_recv.recv() => match command {
+                            Some(command) => self.handle_network_message(command).await,
+                            None => return,
+                        },
+                        _ = tick(&mut self.head_poll) => self.poll_head().await,
+                    }
+                }
+            },
+            "beacon api network",
+        );
+    }
+
+    async fn handle_network_message(&mut self, message: NetworkCommand) {
+        match message {
+            NetworkCommand::SendRangeRequest { start_slot, .. } => {
+                debug!(start_slot, "Send range request");
+
+                self.send_range_request(RequestId::Range, start_slot, RANGE_REQUEST_COUNT)
+                    .await
+            }
+            NetworkCommand::SendBackfillRangeRequest {
+                start_slot, count, ..
+            } => {
+                debug!(start_slot, count, "Send backfill range request");
+
+                self.send_range_request(RequestId::Backfill, start_slot, count)
+                    .await
+            }
+            NetworkCommand::SendBlockByRootRequest { root, .. } => {
+                self.send_block_by_root_request(root).await
+            }
+            NetworkCommand::SubscribeToBlocks => {
+                if self.head_poll.is_none() {
+                    self.head_poll = Some(interval(Duration::from_secs(self.seconds_per_slot)));
+                }
+            }
+            NetworkCommand::ReportPeer(_, reason) => {
+                warn!("The beacon node has been reported: {reason}");
+            }
+        }
+    }
+
+    /// The blocks are fetched concurrently but sent in order, as a peer would stream them
+    async fn send_range_request(&self, id: RequestId, start_slot: u64, count: u64) {
+        let mut blocks = stream::iter(start_slot..start_slot + count)
+            .map(|slot| async move {
+                let block = self.get_block(BlockId::Slot(Slot::new(slot))).await;
+
+                (slot, block)
+            })
+            .buffered(CONCURRENT_BLOCK_REQUESTS);
+
+        while let Some((slot, block)) = blocks.next().await {
+            match block {
+                Ok(Some(block)) => self.send(
+                    id,
+                    NetworkEvent::ResponseReceived {
+                        peer_id: self.peer_id,
+                        id,
+                        response: Response::BlocksByRange(Some(block)),
+                    },
+                ),
+                // Missed slot
+                Ok(None) => {}
+                Err(err) => {
+                    warn!(slot, "Range request failed: {err}");
+
+                    sleep(RETRY_DELAY).await;
+
+                    return self.send(
+                        id,
+                        NetworkEvent::RPCFailed {
+                            id,
+                            peer_id: self.peer_id,
+                        },
+                    );
+                }
+            }
+        }
+
+        self.send(
+            id,
+            NetworkEvent::ResponseReceived {
+                peer_id: self.peer_id,
+                id,
+                response: Response::BlocksByRange(None),
+            },
+        );
+    }
+
+    async fn send_block_by_root_request(&self, root: Hash256) {
+        let id = RequestId::Block(root);
+
+        match self.get_block(BlockId::Root(root)).await {
+            Ok(block) => {
+                if let Some(block) = block {
+                    self.send(
+                        id,
+                        NetworkEvent::ResponseReceived {
+                            peer_id: self.peer_id,
+                            id,
+                            response: Response::BlocksByRoot(Some(block)),
+                        },
+                    );
+                }
+
+                self.send(
+                    id,
+                    NetworkEvent::ResponseReceived {
+                        peer_id: self.peer_id,
+                        id,
+                        response: Response::BlocksByRoot(None),
+                    },
+                );
+            }
+            Err(err) => {
+                debug!(%root, "Block by root request failed: {err}");
+
+                self.send(
+                    id,
+                    NetworkEvent::RPCFailed {
+                        id,
+                        peer_id: self.peer_id,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Reports a new head block the same way a gossip block would be
+    async fn poll_head(&mut self) {
+        let block = match self.get_block(BlockId::Head).await {
+            Ok(Some(block)) => block,
+            Ok(None) => return,
+            Err(err) => {
+                warn!("Unable to get the head block: {err}");
+                return;
+            }
+        };
+
+        let root = block.canonical_root();
+
+        if self.head_root.replace(root) == Some(root) {
+            return;
+        }
+
+        self.send(
+            RequestId::Range,
+            NetworkEvent::PubsubMessage {
+                id: MessageId::new(root.as_bytes()),
+                source: self.peer_id,
+                topic: TopicHash::from_raw("beacon_api_head"),
+                message: PubsubMessage::BeaconBlock(block),
+            },
+        );
+    }
+
+    async fn get_block(
+        &self,
+        block_id: BlockId,
+    ) -> Result<Option<Arc<SignedBeaconBlock<E>>>, String> {
+        self.client
+            .get_block::<E>(block_id)
+            .await
+            .map(|response| response.map(|response| Arc::new(response.data)))
+            .map_err(|err| format!("{err:?}"))
+    }
+
+    fn send(&self, id: RequestId, event: NetworkEvent<RequestId, E>) {
+        // The backfill worker may not be running, so its events can be dropped
+        let _ = match id {
+            RequestId::Backfill => self.backfill_event_send.send(event),
+            _ => self.event_send.send(event),
+        };
+    }
+}
+
+/// Never completes when the head isn't followed
+async fn tick(head_poll: &mut Option<Interval>) {
+    match head_poll {
+        Some(head_poll) => {
+            head_poll.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
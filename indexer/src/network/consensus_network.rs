@@ -3,8 +3,9 @@ use std::{collections::HashMap, sync::Arc};
 use lighthouse_network::{
     rpc::{BlocksByRangeRequest, BlocksByRootRequest, StatusMessage},
     service::Network,
-    Context, Multiaddr, NetworkConfig, NetworkEvent, PeerAction, PeerId, ReportSource, Request,
-    Response,
+    types::GossipKind,
+    Context, MessageAcceptance, Multiaddr, NetworkConfig, NetworkEvent, PeerAction, PeerId,
+    ReportSource, Request, Response,
 };
 use slog::{o, Logger};
 use store::{EnrForkId, Epoch, EthSpec, ForkContext, Hash256, Slot};
@@ -19,6 +20,7 @@ struct ConsensusNetwork<E: EthSpec> {
     service: Network<RequestId, E>,
     command_recv: UnboundedReceiver<NetworkCommand>,
     event_send: UnboundedSender<NetworkEvent<RequestId, E>>,
+    backfill_event_send: UnboundedSender<NetworkEvent<RequestId, E>>,
     peer_db: PeerDb<E>,
     enr_fork_id: EnrForkId,
 }
@@ -27,6 +29,7 @@ struct ConsensusNetwork<E: EthSpec> {
 pub enum RequestId {
     Range,
     Block(Hash256),
+    Backfill,
 }
 
 #[derive(Debug, Clone)]
@@ -35,10 +38,16 @@ pub enum NetworkCommand {
         peer_id: Option<PeerId>,
         start_slot: u64,
     },
+    SendBackfillRangeRequest {
+        peer_id: PeerId,
+        start_slot: u64,
+        count: u64,
+    },
     SendBlockByRootRequest {
         peer_id: Option<PeerId>,
         root: Hash256,
     },
+    SubscribeToBlocks,
     ReportPeer(PeerId, &'static str),
 }
 
@@ -50,6 +59,7 @@ pub async fn spawn<E: EthSpec>(
     (
         UnboundedSender<NetworkCommand>,
         UnboundedReceiver<NetworkEvent<RequestId, E>>,
+        UnboundedReceiver<NetworkEvent<RequestId, E>>,
     ),
     String,
 > {
@@ -83,6 +93,8 @@ pub async fn spawn<E: EthSpec>(
 
     let (command_send, command_recv) = mpsc::unbounded_channel::<NetworkCommand>();
     let (event_send, event_recv) = mpsc::unbounded_channel::<NetworkEvent<RequestId, E>>();
+    let (backfill_event_send, backfill_event_recv) =
+        mpsc::unbounded_channel::<NetworkEvent<RequestId, E>>();
 
     // launch libp2p service
     let (libp2p, network_globals) = Network::new(executor.clone(), service_context, &network_log)
@@ -93,13 +105,14 @@ pub async fn spawn<E: EthSpec>(
         service: libp2p,
         command_recv,
         event_send,
+        backfill_event_send,
         peer_db: PeerDb::new(network_globals, good_peers),
         enr_fork_id: beacon_context.current_fork_id(),
     };
 
     network_service.spawn(executor);
 
-    Ok((command_send, event_recv))
+    Ok((command_send, event_recv, backfill_event_recv))
 }
 
 impl<E: EthSpec> ConsensusNetwork<E> {
@@ -124,10 +137,20 @@ impl<E: EthSpec> ConsensusNetwork<E> {
             }
         }
 
+        // Gossip blocks are only indexed, so they aren't propagated
+        if let NetworkEvent::PubsubMessage { id, source, .. } = &event {
+            self.service.report_message_validation_result(
+                source,
+                id.clone(),
+                MessageAcceptance::Ignore,
+            );
+        }
+
         if let NetworkEvent::RPCFailed { id, peer_id } = event {
             let reason = match id {
                 RequestId::Range => "Range request failed",
                 RequestId::Block(_) => "Block by root request failed",
+                RequestId::Backfill => "Backfill range request failed",
             };
             if self.peer_db.is_good_peer(&peer_id) {
                 warn!(peer = %peer_id, "Connection to good peer failed");
@@ -153,6 +176,36 @@ impl<E: EthSpec> ConsensusNetwork<E> {
                 }),
             ),
 
+            // The backfill worker may not be running, so its events can be dropped
+            NetworkEvent::PeerConnectedOutgoing(peer_id) => {
+                let _ = self
+                    .backfill_event_send
+                    .send(NetworkEvent::PeerConnectedOutgoing(peer_id));
+                self.event_send
+                    .send(NetworkEvent::PeerConnectedOutgoing(peer_id))
+                    .unwrap();
+            }
+
+            NetworkEvent::PeerDisconnected(peer_id) => {
+                let _ = self
+                    .backfill_event_send
+                    .send(NetworkEvent::PeerDisconnected(peer_id));
+                self.event_send
+                    .send(NetworkEvent::PeerDisconnected(peer_id))
+                    .unwrap();
+            }
+
+            event @ (NetworkEvent::RPCFailed {
+                id: RequestId::Backfill,
+                ..
+            }
+            | NetworkEvent::ResponseReceived {
+                id: RequestId::Backfill,
+                ..
+            }) => {
+                let _ = self.backfill_event_send.send(event);
+            }
+
             event => {
                 self.event_send.send(event).unwrap();
             }
@@ -165,9 +218,19 @@ impl<E: EthSpec> ConsensusNetwork<E> {
                 peer_id,
                 start_slot,
             } => self.send_range_request(peer_id, start_slot),
+            NetworkCommand::SendBackfillRangeRequest {
+                peer_id,
+                start_slot,
+                count,
+            } => self.send_backfill_range_request(peer_id, start_slot, count),
             NetworkCommand::SendBlockByRootRequest { peer_id, root } => {
                 self.send_block_by_root_request(peer_id, root)
             }
+            NetworkCommand::SubscribeToBlocks => {
+                if !self.service.subscribe_kind(GossipKind::BeaconBlock) {
+                    warn!("Unable to subscribe to the block gossip topic");
+                }
+            }
             NetworkCommand::ReportPeer(peer_id, reason) => {
                 warn!(peer = %peer_id, "{}", reason);
                 self.service
@@ -194,6 +257,14 @@ impl<E: EthSpec> ConsensusNetwork<E> {
         }
     }
 
+    fn send_backfill_range_request(&mut self, to: PeerId, start_slot: u64, count: u64) {
+        debug!(start_slot, count, "Send backfill range request");
+
+        let request = Request::BlocksByRange(BlocksByRangeRequest { start_slot, count });
+
+        self.service.send_request(to, RequestId::Backfill, request);
+    }
+
     fn send_block_by_root_request(&mut self, to: Option<PeerId>, root: Hash256) {
         let peers = match to {
             Some(to) => vec![to],
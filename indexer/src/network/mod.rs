@@ -1,6 +1,9 @@
+mod beacon_api_network;
 mod consensus_network;
 mod execution_network;
 
+pub use beacon_api_network::spawn as spawn_beacon_api_network;
+
 pub use consensus_network::{
     spawn as spawn_consensus_network, NetworkCommand as ConsensusNetworkCommand, RequestId,
 };
@@ -9,3 +12,10 @@ pub use execution_network::{
     spawn as spawn_execution_network, NetworkCommand as ExecutionNetworkCommand,
     NetworkEvent as ExecutionNetworkEvent,
 };
+
+/// Where the consensus blocks are fetched from
+#[derive(Debug, Clone)]
+pub enum Source {
+    P2p,
+    BeaconApi { beacon_node_url: String },
+}
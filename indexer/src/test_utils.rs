@@ -12,17 +12,24 @@ use slog::{o, Logger};
 use store::MemoryStore;
 use tracing_slog::TracingSlogDrain;
 
-use crate::{beacon_chain::beacon_context::BeaconContext, db::Stores};
+use crate::{
+    beacon_chain::beacon_context::{load_eth2_network_config, BeaconContext},
+    db::{Stores, DEFAULT_REORG_DEPTH},
+};
 
 pub fn build_stores(spec: ChainSpec) -> Arc<Stores<MainnetEthSpec>> {
     let logger = Logger::root(TracingSlogDrain, o!());
-    let beacon_context = BeaconContext::build(spec).unwrap();
+    let beacon_context =
+        BeaconContext::build(load_eth2_network_config("mainnet").unwrap(), spec).unwrap();
 
     Arc::new(Stores::new(
         String::from(""),
         beacon_context.genesis_state.clone(),
         100_000,
         vec![],
+        None,
+        DEFAULT_REORG_DEPTH,
+        beacon_context.spec,
     ))
 }
 
@@ -75,4 +82,27 @@ impl BeaconChainHarness {
             block
         }
     }
+
+    /// A block at `slot` built on `state` instead of the latest block, to fork the chain
+    pub async fn make_fork_block(
+        &self,
+        state: BeaconState<MainnetEthSpec>,
+        slot: u64,
+    ) -> SignedBeaconBlock<MainnetEthSpec> {
+        let (block, _) = self.harness.make_block(state, Slot::new(slot)).await;
+
+        block
+    }
+}
+
+/// A new empty directory for the models persisted by a test
+pub fn temp_base_dir(name: &str) -> String {
+    let dir = std::env::temp_dir()
+        .join("beacon_indexer_tests")
+        .join(format!("{name}_{}", std::process::id()));
+
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    dir.to_string_lossy().into_owned()
 }
@@ -0,0 +1,314 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+};
+
+use lighthouse_types::{Attestation, EthSpec};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use types::{
+    attestation_performance::{AttestationPerformanceModel, AttestationPerformanceModelsWithId},
+    path::{FromPath, Prefix, ToPath},
+    persistable::{MsgPackSerializable, ResolvablePersistable},
+};
+
+use super::consolidated_block::{AttestationCorrectness, ConsolidatedBlock};
+
+/// The attestation duties of the epochs whose attestations can still be included, by validator
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AttestationPerformances {
+    epochs: BTreeMap<u64, HashMap<u64, AttestationPerformanceModel>>,
+}
+
+impl AttestationPerformances {
+    /// The open epochs saved when the indexer stopped. The file is removed once read, so that
+    /// these epochs are never restored over the ones persisted after them.
+    pub fn restore(base_dir: &str) -> Self {
+        let path = Self::to_path(base_dir, &());
+
+        if !Path::new(&path).exists() {
+            return Self::default();
+        }
+
+        let attestation_performances = Self::from_path(base_dir, &()).unwrap_or_else(|err| {
+            warn!("Ignoring the saved attestation performances: {err}");
+            Self::default()
+        });
+
+        if let Err(err) = fs::remove_file(&path) {
+            warn!("Unable to remove '{path}': {err}");
+        }
+
+        attestation_performances
+    }
+
+    /// Saves the open epochs when the indexer stops, as their duties are only persisted once closed
+    pub fn save(&self, base_dir: &str) -> Result<(), String> {
+        self.serialize_to_file(&Self::to_path(base_dir, &()))
+    }
+
+    /// Registers the duties of the block committees, then the inclusions of its attestations
+    pub fn consolidate<E: EthSpec>(
+        &mut self,
+        block: &ConsolidatedBlock<E>,
+        attestations: &[(Attestation<E>, AttestationCorrectness, Vec<u64>)],
+    ) {
+        let epoch = block.epoch().as_u64();
+        let slot = block.slot().as_u64();
+        let duties = self.epochs.entry(epoch).or_default();
+
+        block.committees().iter().for_each(|committee| {
+            committee.committee.iter().for_each(|validator_index| {
+                duties.entry(*validator_index as u64).or_insert_with(|| {
+                    AttestationPerformanceModel::missed(epoch, slot, committee.index)
+                });
+            })
+        });
+
+        attestations
+            .iter()
+            .for_each(|(attestation, correctness, attesting_indices)| {
+                self.include(slot, attestation, *correctness, attesting_indices)
+            });
+    }
+
+    fn include<E: EthSpec>(
+        &mut self,
+        included_in: u64,
+        attestation: &Attestation<E>,
+        correctness: AttestationCorrectness,
+        attesting_indices: &[u64],
+    ) {
+        let epoch = attestation.data.target.epoch.as_u64();
+        let slot = attestation.data.slot.as_u64();
+        let duties = self.epochs.entry(epoch).or_default();
+
+        attesting_indices.iter().for_each(|validator_index| {
+            let duty = duties.entry(*validator_index).or_insert_with(|| {
+                AttestationPerformanceModel::missed(epoch, slot, attestation.data.index)
+            });
+
+            // Only the earliest inclusion counts
+            if duty.is_missed() {
+                duty.included_in = Some(included_in);
+                duty.inclusion_distance = Some(included_in.saturating_sub(slot));
+                duty.correct_source = true;
+                duty.correct_target = correctness.target;
+                duty.correct_head = correctness.head;
+            }
+        });
+    }
+
+    /// Forgets the inclusions of a block which isn't canonical anymore
+    pub fn remove_inclusions(&mut self, included_in: u64) {
+        self.epochs
+            .values_mut()
+            .flat_map(|duties| duties.values_mut())
+            .filter(|duty| duty.included_in == Some(included_in))
+            .for_each(|duty| {
+                *duty =
+                    AttestationPerformanceModel::missed(duty.epoch, duty.slot, duty.committee_index)
+            });
+    }
+
+    /// Persists the epochs whose attestations can't be included anymore at `epoch`, each chunk
+    /// being written once. A duty replaces the one of the same epoch, if it has already been
+    /// persisted.
+    pub fn persist_closed(&mut self, base_dir: &str, epoch: u64) -> Result<(), String> {
+        let open_epochs = self.epochs.split_off(&epoch.saturating_sub(1));
+        let closed_epochs = std::mem::replace(&mut self.epochs, open_epochs);
+        let mut chunks = HashMap::<String, Vec<AttestationPerformanceModel>>::new();
+
+        closed_epochs
+            .into_values()
+            .flat_map(|duties| duties.into_iter())
+            .for_each(|(validator_index, duty)| {
+                chunks
+                    .entry(AttestationPerformanceModel::chunk_id(
+                        validator_index,
+                        duty.epoch,
+                    ))
+                    .or_default()
+                    .push(duty)
+            });
+
+        chunks.into_iter().try_for_each(|(id, duties)| {
+            let mut model =
+                Vec::<AttestationPerformanceModel>::from_path(base_dir, &id).unwrap_or_default();
+
+            model.retain(|d| duties.iter().all(|duty| duty.epoch != d.epoch));
+            model.extend(duties);
+            model.sort_by_key(|duty| duty.epoch);
+
+            AttestationPerformanceModelsWithId { id, model }.save(base_dir)
+        })
+    }
+}
+
+impl ToPath for AttestationPerformances {
+    type Id = ();
+
+    fn to_path(base_dir: &str, _: &Self::Id) -> String {
+        format!(
+            "{base_dir}{}/open.msg",
+            AttestationPerformanceModel::prefix()
+        )
+    }
+}
+
+impl MsgPackSerializable for AttestationPerformances {}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use lighthouse_types::{
+        AggregateSignature, Attestation, AttestationData, BitList, Checkpoint, EthSpec, Hash256,
+        MainnetEthSpec, Slot,
+    };
+    use types::{
+        attestation_performance::AttestationPerformanceModel,
+        path::{Dirs, FromPath, ToPath},
+    };
+
+    use super::AttestationPerformances;
+    use crate::{test_utils::temp_base_dir, types::consolidated_block::AttestationCorrectness};
+
+    const CORRECT: AttestationCorrectness = AttestationCorrectness {
+        target: true,
+        head: true,
+    };
+
+    fn attestation(slot: u64) -> Attestation<MainnetEthSpec> {
+        Attestation {
+            aggregation_bits: BitList::with_capacity(1).unwrap(),
+            data: AttestationData {
+                slot: Slot::new(slot),
+                index: 0,
+                beacon_block_root: Hash256::zero(),
+                source: Checkpoint::default(),
+                target: Checkpoint {
+                    epoch: Slot::new(slot).epoch(MainnetEthSpec::slots_per_epoch()),
+                    root: Hash256::zero(),
+                },
+            },
+            signature: AggregateSignature::empty(),
+        }
+    }
+
+    fn duty(
+        attestation_performances: &AttestationPerformances,
+        epoch: u64,
+        validator_index: u64,
+    ) -> &AttestationPerformanceModel {
+        &attestation_performances.epochs[&epoch][&validator_index]
+    }
+
+    #[test]
+    fn test_include() {
+        let mut attestation_performances = AttestationPerformances::default();
+        let head_missed = AttestationCorrectness {
+            target: true,
+            head: false,
+        };
+
+        attestation_performances.include(35, &attestation(33), head_missed, &[1, 2]);
+        attestation_performances.include(36, &attestation(33), CORRECT, &[1, 3]);
+
+        let first = duty(&attestation_performances, 1, 1);
+
+        // Only the earliest inclusion counts
+        assert_eq!(first.included_in, Some(35));
+        assert_eq!(first.inclusion_distance, Some(2));
+        assert!(first.correct_source && first.correct_target && !first.correct_head);
+
+        let later = duty(&attestation_performances, 1, 3);
+
+        assert_eq!(later.included_in, Some(36));
+        assert_eq!(later.inclusion_distance, Some(3));
+        assert!(later.correct_head);
+    }
+
+    #[test]
+    fn test_remove_inclusions() {
+        let mut attestation_performances = AttestationPerformances::default();
+
+        attestation_performances.include(35, &attestation(33), CORRECT, &[1]);
+        attestation_performances.include(36, &attestation(34), CORRECT, &[2]);
+
+        attestation_performances.remove_inclusions(35);
+
+        let removed = duty(&attestation_performances, 1, 1);
+
+        assert!(removed.is_missed());
+        assert_eq!(removed.slot, 33);
+        assert!(!removed.correct_target);
+        assert_eq!(duty(&attestation_performances, 1, 2).included_in, Some(36));
+    }
+
+    #[test]
+    fn test_persist_closed() {
+        let base_dir = temp_base_dir("persist_closed_attestation_performances");
+        AttestationPerformanceModel::create_dirs(&base_dir).unwrap();
+
+        let mut attestation_performances = AttestationPerformances::default();
+
+        attestation_performances.include(2, &attestation(1), CORRECT, &[1]);
+        attestation_performances.include(34, &attestation(33), CORRECT, &[1]);
+        attestation_performances.include(66, &attestation(65), CORRECT, &[1]);
+
+        // The attestations of the previous epoch can still be included
+        attestation_performances
+            .persist_closed(&base_dir, 2)
+            .unwrap();
+
+        assert_eq!(
+            attestation_performances.epochs.keys().collect::<Vec<_>>(),
+            vec![&1, &2]
+        );
+
+        let chunk_id = AttestationPerformanceModel::chunk_id(1, 0);
+        let persisted =
+            Vec::<AttestationPerformanceModel>::from_path(&base_dir, &chunk_id).unwrap();
+
+        assert_eq!(persisted.len(), 1);
+        assert_eq!(persisted[0].included_in, Some(2));
+
+        // A duty of an already persisted epoch replaces the previous one
+        attestation_performances.include(5, &attestation(3), CORRECT, &[1]);
+        attestation_performances
+            .persist_closed(&base_dir, 3)
+            .unwrap();
+
+        let persisted =
+            Vec::<AttestationPerformanceModel>::from_path(&base_dir, &chunk_id).unwrap();
+
+        assert_eq!(
+            persisted
+                .iter()
+                .map(|duty| (duty.epoch, duty.included_in))
+                .collect::<Vec<_>>(),
+            vec![(0, Some(5)), (1, Some(34))]
+        );
+    }
+
+    #[test]
+    fn test_save_and_restore() {
+        let base_dir = temp_base_dir("restore_attestation_performances");
+        AttestationPerformanceModel::create_dirs(&base_dir).unwrap();
+
+        let mut attestation_performances = AttestationPerformances::default();
+
+        attestation_performances.include(35, &attestation(33), CORRECT, &[1]);
+        attestation_performances.save(&base_dir).unwrap();
+
+        let restored = AttestationPerformances::restore(&base_dir);
+
+        assert_eq!(duty(&restored, 1, 1).included_in, Some(35));
+        assert!(!Path::new(&AttestationPerformances::to_path(&base_dir, &())).exists());
+        assert!(AttestationPerformances::restore(&base_dir)
+            .epochs
+            .is_empty());
+    }
+}
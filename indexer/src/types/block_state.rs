@@ -4,7 +4,7 @@ use std::{
 };
 
 use lighthouse_types::{Epoch, EthSpec, Hash256, SignedBeaconBlock, Slot};
-use types::block::BlockExtendedModel;
+use types::{block::BlockExtendedModel, graffiti};
 
 #[derive(Debug, Clone)]
 pub enum BlockState<E: EthSpec> {
@@ -77,6 +77,7 @@ impl<E: EthSpec> From<&BlockState<E>> for Option<BlockExtendedModel> {
             eth1data_deposit_root: format!("{:?}", block.message().body().eth1_data().deposit_root),
             eth1data_deposit_count: block.message().body().eth1_data().deposit_count,
             eth1data_block_hash: format!("{:?}", block.message().body().eth1_data().block_hash),
+            graffiti_text: graffiti::decode(&block.message().body().graffiti().0),
         })
     }
 }
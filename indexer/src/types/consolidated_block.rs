@@ -1,9 +1,10 @@
 use std::sync::Arc;
 
 use lighthouse_types::{
-    Attestation, DepositData as LighthouseDepositData, Epoch, EthSpec, OwnedBeaconCommittee, Slot,
+    Attestation, DepositData as LighthouseDepositData, Epoch, EthSpec, ExecutionBlockHash, Hash256,
+    OwnedBeaconCommittee, SignedBlsToExecutionChange, SignedVoluntaryExit, Slot, Withdrawal,
 };
-use shared::utils::clock::Clock;
+use serde::{Deserialize, Serialize};
 use store::SignedBeaconBlock;
 use types::{
     attestation::{AttestationModel, AttestationModelsWithId},
@@ -11,25 +12,63 @@ use types::{
     block_root::{BlockRootModel, BlockRootModelWithId},
     committee::{CommitteeModel, CommitteeModelsWithId},
     deposit::{ConsensusLayerDepositModel, DepositData},
+    execution_payload::{ExecutionPayloadModel, ExecutionPayloadModelWithId},
+    graffiti,
+    search::{hex_key, text_keys, SearchResult},
+    slashing::{
+        AttesterSlashingModel, AttesterSlashingModelsWithId, ProposerSlashingModel,
+        ProposerSlashingModelsWithId, SlashingModel,
+    },
+    sync_committee::{SyncAggregateModel, SyncAggregateModelWithId},
+    voluntary_exit::{IndexedVoluntaryExitModel, VoluntaryExitModel, VoluntaryExitModelsWithId},
+    withdrawal::{
+        BlsToExecutionChangeModel, BlsToExecutionChangeModelsWithId, WithdrawalModel,
+        WithdrawalModelsWithId,
+    },
 };
 
-use super::block_state::BlockState;
+use super::{block_state::BlockState, consolidated_sync_committees::ConsolidatedSyncCommittees};
+
+/// Whether an included attestation voted for the canonical target and head.
+/// Its source is always correct, otherwise it couldn't have been included.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AttestationCorrectness {
+    pub target: bool,
+    pub head: bool,
+}
+
+/// The global ids of the next voluntary exit and slashing of the canonical chain. They're derived
+/// from the chain, so that rolled back or replayed blocks number theirs the same way.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationIds {
+    pub voluntary_exit: u64,
+    #[serde(default)]
+    pub slashing: u64,
+}
 
 #[derive(Debug, Clone)]
 pub struct ConsolidatedBlock<E: EthSpec> {
     block: BlockState<E>,
     epoch: Epoch,
     slot: Slot,
+    timestamp: u64,
     proposer_index: u64,
     committees: Vec<OwnedBeaconCommittee>,
+    attestations_correctness: Vec<AttestationCorrectness>,
+    sync_committees: Option<ConsolidatedSyncCommittees>,
     pub deposits: Vec<LighthouseDepositData>,
+    first_operation_ids: OperationIds,
+    displaced: bool,
 }
 
 impl<E: EthSpec> ConsolidatedBlock<E> {
     pub fn new(
         block: BlockState<E>,
+        timestamp: u64,
         proposer_index: u64,
         committees: Vec<OwnedBeaconCommittee>,
+        attestations_correctness: Vec<AttestationCorrectness>,
+        sync_committees: Option<ConsolidatedSyncCommittees>,
         deposits: Vec<LighthouseDepositData>,
     ) -> Self {
         let slot = block.slot();
@@ -38,16 +77,97 @@ impl<E: EthSpec> ConsolidatedBlock<E> {
             block,
             epoch: slot.epoch(E::slots_per_epoch()),
             slot,
+            timestamp,
             proposer_index,
             committees,
+            attestations_correctness,
+            sync_committees,
             deposits,
+            first_operation_ids: OperationIds::default(),
+            displaced: false,
         }
     }
 
+    pub fn orphaned(block: Arc<SignedBeaconBlock<E>>, timestamp: u64) -> Self {
+        let proposer_index = block.message().proposer_index();
+
+        Self::new(
+            BlockState::Orphaned(block),
+            timestamp,
+            proposer_index,
+            vec![],
+            vec![],
+            None,
+            vec![],
+        )
+    }
+
+    /// A block which was canonical before a reorg. Whether its proposal was missed is only
+    /// known once the new chain has been processed.
+    pub fn displaced(block: Arc<SignedBeaconBlock<E>>, timestamp: u64) -> Self {
+        ConsolidatedBlock {
+            displaced: true,
+            ..Self::orphaned(block, timestamp)
+        }
+    }
+
+    /// Numbers the operations of the block after the ones of the chain before it
+    pub fn with_first_operation_ids(mut self, first_operation_ids: OperationIds) -> Self {
+        self.first_operation_ids = first_operation_ids;
+        self
+    }
+
     pub fn slot(&self) -> Slot {
         self.slot
     }
 
+    pub fn epoch(&self) -> Epoch {
+        self.epoch
+    }
+
+    pub fn proposer_index(&self) -> u64 {
+        self.proposer_index
+    }
+
+    pub fn committees(&self) -> &[OwnedBeaconCommittee] {
+        &self.committees
+    }
+
+    pub fn sync_committees(&self) -> Option<&ConsolidatedSyncCommittees> {
+        self.sync_committees.as_ref()
+    }
+
+    pub fn block(&self) -> &BlockState<E> {
+        &self.block
+    }
+
+    pub fn root(&self) -> Option<Hash256> {
+        self.block.root()
+    }
+
+    pub fn is_orphaned(&self) -> bool {
+        matches!(self.block, BlockState::Orphaned(_))
+    }
+
+    pub fn is_displaced(&self) -> bool {
+        self.displaced
+    }
+
+    pub fn first_operation_ids(&self) -> OperationIds {
+        self.first_operation_ids
+    }
+
+    /// The ids of the operations of the chain following the ones of the block
+    pub fn next_operation_ids(&self) -> OperationIds {
+        OperationIds {
+            voluntary_exit: self.first_operation_ids.voluntary_exit
+                + self.get_voluntary_exits_count() as u64,
+            slashing: self.first_operation_ids.slashing
+                + (self.get_proposer_slashings_count() + self.get_attester_slashings_count())
+                    as u64,
+        }
+    }
+
     pub fn attestations(&self) -> Vec<Attestation<E>> {
         match &self.block {
             BlockState::Proposed(block) => block.message().body().attestations().to_vec(),
@@ -55,37 +175,134 @@ impl<E: EthSpec> ConsolidatedBlock<E> {
         }
     }
 
+    /// The attestations of a canonical block along with their correctness
+    pub fn attestations_with_correctness(&self) -> Vec<(Attestation<E>, AttestationCorrectness)> {
+        self.attestations()
+            .into_iter()
+            .enumerate()
+            .map(|(i, attestation)| {
+                let correctness = self
+                    .attestations_correctness
+                    .get(i)
+                    .copied()
+                    .unwrap_or_default();
+
+                (attestation, correctness)
+            })
+            .collect()
+    }
+
+    /// The attestations an orphaned block included, whose votes don't count
+    pub fn orphaned_attestations(&self) -> Vec<Attestation<E>> {
+        match &self.block {
+            BlockState::Orphaned(block) => block.message().body().attestations().to_vec(),
+            _ => vec![],
+        }
+    }
+
+    /// The withdrawals of the execution payload, for Capella blocks and later
+    pub fn withdrawals(&self) -> Vec<Withdrawal> {
+        match &self.block {
+            BlockState::Proposed(block) | BlockState::Orphaned(block) => block
+                .message()
+                .execution_payload()
+                .ok()
+                .and_then(|payload| payload.execution_payload_ref().withdrawals().ok())
+                .map(|withdrawals| withdrawals.to_vec())
+                .unwrap_or_default(),
+            _ => vec![],
+        }
+    }
+
+    pub fn voluntary_exits(&self) -> Vec<SignedVoluntaryExit> {
+        match &self.block {
+            BlockState::Proposed(block) | BlockState::Orphaned(block) => {
+                block.message().body().voluntary_exits().to_vec()
+            }
+            _ => vec![],
+        }
+    }
+
+    pub fn bls_to_execution_changes(&self) -> Vec<SignedBlsToExecutionChange> {
+        match &self.block {
+            BlockState::Proposed(block) | BlockState::Orphaned(block) => block
+                .message()
+                .body()
+                .bls_to_execution_changes()
+                .map(|changes| changes.to_vec())
+                .unwrap_or_default(),
+            _ => vec![],
+        }
+    }
+
+    /// The graffiti of the block, if it's valid UTF-8
+    pub fn graffiti(&self) -> Option<String> {
+        match &self.block {
+            BlockState::Proposed(block) | BlockState::Orphaned(block) => {
+                graffiti::decode(&block.message().body().graffiti().0)
+            }
+            _ => None,
+        }
+    }
+
+    /// The search keys resolving to the block: its root, its state root and its graffiti words
+    pub fn search_entries(&self) -> Vec<(String, SearchResult)> {
+        let result = SearchResult::Block(self.slot.as_u64());
+
+        match &self.block {
+            BlockState::Proposed(block) | BlockState::Orphaned(block) => vec![
+                hex_key(&format!("{:?}", block.canonical_root())),
+                hex_key(&format!("{:?}", block.message().state_root())),
+            ]
+            .into_iter()
+            .chain(text_keys(&self.graffiti().unwrap_or_default()))
+            .map(|key| (key, result))
+            .collect(),
+            _ => vec![],
+        }
+    }
+
     pub fn get_attestations_count(&self) -> usize {
         match &self.block {
-            BlockState::Proposed(block) => block.message().body().attestations().len(),
+            BlockState::Proposed(block) | BlockState::Orphaned(block) => {
+                block.message().body().attestations().len()
+            }
             _ => 0,
         }
     }
 
     pub fn get_deposits_count(&self) -> usize {
         match &self.block {
-            BlockState::Proposed(block) => block.message().body().deposits().len(),
+            BlockState::Proposed(block) | BlockState::Orphaned(block) => {
+                block.message().body().deposits().len()
+            }
             _ => 0,
         }
     }
 
     pub fn get_voluntary_exits_count(&self) -> usize {
         match &self.block {
-            BlockState::Proposed(block) => block.message().body().voluntary_exits().len(),
+            BlockState::Proposed(block) | BlockState::Orphaned(block) => {
+                block.message().body().voluntary_exits().len()
+            }
             _ => 0,
         }
     }
 
     pub fn get_proposer_slashings_count(&self) -> usize {
         match &self.block {
-            BlockState::Proposed(block) => block.message().body().proposer_slashings().len(),
+            BlockState::Proposed(block) | BlockState::Orphaned(block) => {
+                block.message().body().proposer_slashings().len()
+            }
             _ => 0,
         }
     }
 
     pub fn get_attester_slashings_count(&self) -> usize {
         match &self.block {
-            BlockState::Proposed(block) => block.message().body().attester_slashings().len(),
+            BlockState::Proposed(block) | BlockState::Orphaned(block) => {
+                block.message().body().attester_slashings().len()
+            }
             _ => 0,
         }
     }
@@ -93,12 +310,9 @@ impl<E: EthSpec> ConsolidatedBlock<E> {
 
 impl<E: EthSpec> From<&ConsolidatedBlock<E>> for BlockModelWithId {
     fn from(value: &ConsolidatedBlock<E>) -> Self {
-        let spec = E::default_spec();
-        let clock = Clock::new(spec);
-
         let model = BlockModel {
             epoch: value.epoch.as_u64(),
-            timestamp: clock.timestamp(value.slot).unwrap_or(0),
+            timestamp: value.timestamp,
             proposer_slashings_count: value.get_proposer_slashings_count(),
             attester_slashings_count: value.get_attester_slashings_count(),
             attestations_count: value.get_attestations_count(),
@@ -168,6 +382,205 @@ impl<E: EthSpec> From<&ConsolidatedBlock<E>> for CommitteeModelsWithId {
     }
 }
 
+impl<E: EthSpec> From<&ConsolidatedBlock<E>> for SyncAggregateModelWithId {
+    fn from(value: &ConsolidatedBlock<E>) -> Self {
+        let model = value
+            .block
+            .canonical_block()
+            .and_then(|block| block.message().body().sync_aggregate().ok())
+            .zip(value.sync_committees.as_ref())
+            .map(|(sync_aggregate, sync_committees)| {
+                let committee_bits = sync_aggregate
+                    .sync_committee_bits
+                    .iter()
+                    .collect::<Vec<bool>>();
+
+                SyncAggregateModel {
+                    period: sync_committees.period,
+                    participants: sync_committees
+                        .current
+                        .iter()
+                        .zip(&committee_bits)
+                        .filter(|(_, signed)| **signed)
+                        .map(|(validator_index, _)| *validator_index)
+                        .collect(),
+                    committee_bits,
+                    signature: sync_aggregate.sync_committee_signature.to_string(),
+                }
+            });
+
+        SyncAggregateModelWithId {
+            id: value.slot.as_u64(),
+            model,
+        }
+    }
+}
+
+impl<E: EthSpec> From<&ConsolidatedBlock<E>> for Option<ExecutionPayloadModelWithId> {
+    fn from(value: &ConsolidatedBlock<E>) -> Self {
+        let block: Option<Arc<SignedBeaconBlock<E>>> = (&value.block).into();
+        let block = block?;
+        let payload = block
+            .message()
+            .execution_payload()
+            .ok()?
+            .execution_payload_ref();
+
+        // Before the Merge, Bellatrix blocks carry an empty payload
+        if payload.block_hash() == ExecutionBlockHash::zero() {
+            return None;
+        }
+
+        Some(ExecutionPayloadModelWithId {
+            id: value.slot.as_u64(),
+            model: ExecutionPayloadModel {
+                block_number: payload.block_number(),
+                block_hash: format!("{:?}", payload.block_hash()),
+                parent_hash: format!("{:?}", payload.parent_hash()),
+                fee_recipient: format!("{:?}", payload.fee_recipient()),
+                gas_used: payload.gas_used(),
+                gas_limit: payload.gas_limit(),
+                base_fee_per_gas: payload.base_fee_per_gas().to_string(),
+                timestamp: payload.timestamp(),
+                transactions_count: payload.transactions().len(),
+                extra_data: format!(
+                    "0x{}",
+                    payload
+                        .extra_data()
+                        .iter()
+                        .map(|byte| format!("{byte:02x}"))
+                        .collect::<String>()
+                ),
+            },
+        })
+    }
+}
+
+impl<E: EthSpec> From<&ConsolidatedBlock<E>> for WithdrawalModelsWithId {
+    fn from(value: &ConsolidatedBlock<E>) -> Self {
+        WithdrawalModelsWithId {
+            id: value.slot.as_u64(),
+            model: value
+                .withdrawals()
+                .iter()
+                .map(WithdrawalModel::from)
+                .collect(),
+        }
+    }
+}
+
+impl<E: EthSpec> From<&ConsolidatedBlock<E>> for BlsToExecutionChangeModelsWithId {
+    fn from(value: &ConsolidatedBlock<E>) -> Self {
+        BlsToExecutionChangeModelsWithId {
+            id: value.slot.as_u64(),
+            model: value
+                .bls_to_execution_changes()
+                .iter()
+                .map(BlsToExecutionChangeModel::from)
+                .collect(),
+        }
+    }
+}
+
+impl<E: EthSpec> From<&ConsolidatedBlock<E>> for VoluntaryExitModelsWithId {
+    fn from(value: &ConsolidatedBlock<E>) -> Self {
+        VoluntaryExitModelsWithId {
+            id: value.slot.as_u64(),
+            model: value
+                .voluntary_exits()
+                .iter()
+                .map(VoluntaryExitModel::from)
+                .collect(),
+        }
+    }
+}
+
+impl<E: EthSpec> From<&ConsolidatedBlock<E>> for Vec<IndexedVoluntaryExitModel> {
+    fn from(value: &ConsolidatedBlock<E>) -> Self {
+        value
+            .block
+            .canonical_block()
+            .map(|block| {
+                block
+                    .message()
+                    .body()
+                    .voluntary_exits()
+                    .iter()
+                    .map(|voluntary_exit| IndexedVoluntaryExitModel {
+                        slot: value.slot.as_u64(),
+                        voluntary_exit: VoluntaryExitModel::from(voluntary_exit),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl<E: EthSpec> From<&ConsolidatedBlock<E>> for ProposerSlashingModelsWithId {
+    fn from(value: &ConsolidatedBlock<E>) -> Self {
+        let block: Option<Arc<SignedBeaconBlock<E>>> = (&value.block).into();
+
+        ProposerSlashingModelsWithId {
+            id: value.slot.as_u64(),
+            model: block
+                .map(|block| {
+                    block
+                        .message()
+                        .body()
+                        .proposer_slashings()
+                        .iter()
+                        .map(ProposerSlashingModel::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl<E: EthSpec> From<&ConsolidatedBlock<E>> for AttesterSlashingModelsWithId {
+    fn from(value: &ConsolidatedBlock<E>) -> Self {
+        let block: Option<Arc<SignedBeaconBlock<E>>> = (&value.block).into();
+
+        AttesterSlashingModelsWithId {
+            id: value.slot.as_u64(),
+            model: block
+                .map(|block| {
+                    block
+                        .message()
+                        .body()
+                        .attester_slashings()
+                        .iter()
+                        .map(AttesterSlashingModel::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// The slashings of a canonical block, proposer slashings first as in the block processing
+impl<E: EthSpec> From<&ConsolidatedBlock<E>> for Vec<SlashingModel> {
+    fn from(value: &ConsolidatedBlock<E>) -> Self {
+        if value.block.canonical_block().is_none() {
+            return vec![];
+        }
+
+        let slot = value.slot.as_u64();
+
+        ProposerSlashingModelsWithId::from(value)
+            .model
+            .iter()
+            .map(|slashing| SlashingModel::proposer(slot, value.proposer_index, slashing))
+            .chain(
+                AttesterSlashingModelsWithId::from(value)
+                    .model
+                    .iter()
+                    .map(|slashing| SlashingModel::attester(slot, value.proposer_index, slashing)),
+            )
+            .collect()
+    }
+}
+
 impl<E: EthSpec> From<&ConsolidatedBlock<E>> for Vec<ConsensusLayerDepositModel> {
     fn from(value: &ConsolidatedBlock<E>) -> Self {
         value
@@ -4,18 +4,24 @@ use eth2::lighthouse::GlobalValidatorInclusionData;
 
 use lighthouse_types::{Epoch, EthSpec};
 use serde::{Deserialize, Serialize};
-use shared::utils::clock::Clock;
 use state_processing::per_epoch_processing::EpochProcessingSummary;
 
-use types::epoch::{EpochExtendedModel, EpochExtendedModelWithId, EpochModel, EpochModelWithId};
+use types::{
+    epoch::{EpochExtendedModel, EpochExtendedModelWithId, EpochModel, EpochModelWithId},
+    graffiti::{self, GraffitiTallyModel, GraffitiTallyModelWithId},
+    validator::ValidatorModel,
+};
 
 use super::block_state::BlockState;
 
 #[derive(Debug)]
 pub struct ConsolidatedEpoch<E: EthSpec> {
     epoch: Epoch,
+    timestamp: u64,
     aggregated_data: AggregatedEpochData,
     validator_balances: Vec<u64>,
+    validator_effective_balances: Vec<u64>,
+    changed_validators: Vec<(u64, ValidatorModel)>,
     validator_inclusion: GlobalValidatorInclusionData,
     phantom: PhantomData<E>,
 }
@@ -23,14 +29,20 @@ pub struct ConsolidatedEpoch<E: EthSpec> {
 impl<E: EthSpec> ConsolidatedEpoch<E> {
     pub fn new(
         epoch: Epoch,
+        timestamp: u64,
         aggregated_data: AggregatedEpochData,
         summary: &EpochProcessingSummary<E>,
         validator_balances: Vec<u64>,
+        validator_effective_balances: Vec<u64>,
+        changed_validators: Vec<(u64, ValidatorModel)>,
     ) -> Self {
         ConsolidatedEpoch::<E> {
             epoch,
+            timestamp,
             aggregated_data,
             validator_balances,
+            validator_effective_balances,
+            changed_validators,
             validator_inclusion: GlobalValidatorInclusionData {
                 current_epoch_active_gwei: summary.current_epoch_total_active_balance(),
                 previous_epoch_active_gwei: summary.previous_epoch_total_active_balance(),
@@ -52,6 +64,20 @@ impl<E: EthSpec> ConsolidatedEpoch<E> {
         self.epoch.as_usize()
     }
 
+    /// The validators whose model changed since the previous epoch, by index
+    pub fn changed_validators(&self) -> &[(u64, ValidatorModel)] {
+        &self.changed_validators
+    }
+
+    /// The balance and effective balance of every validator at the end of the epoch, by index
+    pub fn validator_balances(&self) -> impl Iterator<Item = (u64, u64, u64)> + '_ {
+        self.validator_balances
+            .iter()
+            .zip(&self.validator_effective_balances)
+            .enumerate()
+            .map(|(i, (balance, effective_balance))| (i as u64, *balance, *effective_balance))
+    }
+
     pub fn get_total_validators_balance(&self) -> u64 {
         self.validator_balances.iter().sum()
     }
@@ -59,17 +85,13 @@ impl<E: EthSpec> ConsolidatedEpoch<E> {
 
 impl<E: EthSpec> From<&ConsolidatedEpoch<E>> for EpochModelWithId {
     fn from(value: &ConsolidatedEpoch<E>) -> Self {
-        let start_slot = value.epoch.start_slot(E::slots_per_epoch());
-        let spec = E::default_spec();
-        let clock = Clock::new(spec);
-
         let eligible_ether = value.validator_inclusion.previous_epoch_active_gwei;
         let voted_ether = value
             .validator_inclusion
             .previous_epoch_target_attesting_gwei;
 
         let model = EpochModel {
-            timestamp: clock.timestamp(start_slot).unwrap_or(0),
+            timestamp: value.timestamp,
             proposed_blocks_count: value.aggregated_data.proposed_blocks_count,
             missed_blocks_count: value.aggregated_data.missed_blocks_count,
             orphaned_blocks_count: value.aggregated_data.orphaned_blocks_count,
@@ -79,6 +101,8 @@ impl<E: EthSpec> From<&ConsolidatedEpoch<E>> for EpochModelWithId {
             deposits_count: value.aggregated_data.deposits_count,
             eligible_ether,
             voted_ether,
+            withdrawals_count: value.aggregated_data.withdrawals_count,
+            bls_to_execution_changes_count: value.aggregated_data.bls_to_execution_changes_count,
         };
 
         EpochModelWithId {
@@ -88,6 +112,15 @@ impl<E: EthSpec> From<&ConsolidatedEpoch<E>> for EpochModelWithId {
     }
 }
 
+impl<E: EthSpec> From<&ConsolidatedEpoch<E>> for GraffitiTallyModelWithId {
+    fn from(value: &ConsolidatedEpoch<E>) -> Self {
+        GraffitiTallyModelWithId {
+            id: value.epoch.as_u64(),
+            model: value.aggregated_data.graffiti.clone(),
+        }
+    }
+}
+
 impl<E: EthSpec> Display for ConsolidatedEpoch<E> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.epoch)
@@ -122,6 +155,12 @@ pub struct AggregatedEpochData {
     pub voluntary_exits_count: usize,
     pub proposer_slashings_count: usize,
     pub attester_slashings_count: usize,
+    #[serde(default)]
+    pub withdrawals_count: usize,
+    #[serde(default)]
+    pub bls_to_execution_changes_count: usize,
+    #[serde(default)]
+    pub graffiti: GraffitiTallyModel,
 }
 
 impl AggregatedEpochData {
@@ -138,9 +177,28 @@ impl AggregatedEpochData {
             self.voluntary_exits_count += block.message().body().voluntary_exits().len();
             self.proposer_slashings_count += block.message().body().proposer_slashings().len();
             self.attester_slashings_count += block.message().body().attester_slashings().len();
+            self.withdrawals_count += block
+                .message()
+                .execution_payload()
+                .ok()
+                .and_then(|payload| payload.execution_payload_ref().withdrawals().ok())
+                .map_or(0, |withdrawals| withdrawals.len());
+            self.bls_to_execution_changes_count += block
+                .message()
+                .body()
+                .bls_to_execution_changes()
+                .map_or(0, |changes| changes.len());
+            self.graffiti
+                .record(graffiti::decode(&block.message().body().graffiti().0).as_deref());
         }
     }
 
+    /// A slot counted as missed turned out to have an orphaned block
+    pub fn orphan_missed_block(&mut self) {
+        self.missed_blocks_count = self.missed_blocks_count.saturating_sub(1);
+        self.orphaned_blocks_count += 1;
+    }
+
     pub fn aggregate(&mut self) -> Self {
         let aggregated = self.clone();
 
@@ -152,6 +210,9 @@ impl AggregatedEpochData {
         self.voluntary_exits_count = 0;
         self.proposer_slashings_count = 0;
         self.attester_slashings_count = 0;
+        self.withdrawals_count = 0;
+        self.bls_to_execution_changes_count = 0;
+        self.graffiti = GraffitiTallyModel::default();
 
         aggregated
     }
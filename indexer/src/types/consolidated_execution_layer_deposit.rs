@@ -1,5 +1,8 @@
 use lighthouse_types::{typenum::U33, DepositData, FixedVector, Hash256};
-use types::deposit::{ExecutionLayerDepositModel, ExecutionLayerDepositModelWithId};
+use types::{
+    deposit::{ExecutionLayerDepositModel, ExecutionLayerDepositModelWithId},
+    search::{hex_key, SearchResult},
+};
 
 #[derive(Debug, Clone)]
 pub struct ConsolidatedExecutionLayerDeposit {
@@ -29,6 +32,19 @@ impl ConsolidatedExecutionLayerDeposit {
             validator_index,
         }
     }
+
+    /// The search keys resolving to the deposit: its public key and withdrawal credentials
+    pub fn search_entries(&self) -> Vec<(String, SearchResult)> {
+        let result = SearchResult::Deposit(self.index);
+
+        vec![
+            (hex_key(&self.deposit_data.pubkey.to_string()), result),
+            (
+                hex_key(&format!("{:?}", self.deposit_data.withdrawal_credentials)),
+                result,
+            ),
+        ]
+    }
 }
 
 impl From<&ConsolidatedExecutionLayerDeposit> for ExecutionLayerDepositModelWithId {
@@ -0,0 +1,145 @@
+use std::{fs, ops::Range};
+
+use lighthouse_types::EthSpec;
+use parking_lot::RwLockWriteGuard;
+use types::{
+    meta::Meta,
+    path::{FromPath, ToPath},
+    persistable::ResolvablePersistable,
+    slashing::{SlashingModel, SlashingModelWithId},
+    utils::MetaCache,
+};
+
+use super::consolidated_block::ConsolidatedBlock;
+
+/// The slashings of a canonical block, numbered after the ones of the chain before it
+pub struct ConsolidatedSlashings<'a> {
+    slashings: Vec<SlashingModel>,
+    ids: Range<u64>,
+    meta_cache: RwLockWriteGuard<'a, MetaCache>,
+}
+
+impl<'a> ConsolidatedSlashings<'a> {
+    /// Returns the saved slashings along with their ids, the count then ending with the block
+    pub fn save(mut self, base_path: &str) -> Result<Vec<SlashingModelWithId>, String> {
+        let first_id = self.ids.start;
+        let mut saved = vec![];
+
+        let result = self
+            .slashings
+            .into_iter()
+            .enumerate()
+            .map(|(i, slashing)| SlashingModelWithId {
+                id: first_id + i as u64,
+                model: slashing,
+            })
+            .try_for_each(|slashing| {
+                slashing.save(base_path)?;
+                saved.push(slashing);
+                Ok::<_, String>(())
+            });
+
+        self.meta_cache
+            .entry::<SlashingModel>()
+            .update_count(first_id as usize + saved.len())
+            .save::<SlashingModel>(base_path)?;
+
+        result.map(|_| saved)
+    }
+
+    /// Removes the slashings of a block displaced by a reorg, the new chain numbering its own
+    /// from the same id. Returns the removed slashings.
+    pub fn rewind(mut self, base_path: &str) -> Result<Vec<SlashingModelWithId>, String> {
+        let meta = self
+            .meta_cache
+            .loaded_entry::<SlashingModel>()
+            .or_insert_with(Meta::default);
+
+        meta.count = meta.count.min(self.ids.start as usize);
+        meta.save::<SlashingModel>(base_path)?;
+
+        self.ids
+            .clone()
+            .filter_map(|id| {
+                SlashingModel::from_path(base_path, &id)
+                    .ok()
+                    .map(|model| SlashingModelWithId { id, model })
+            })
+            .map(|slashing| {
+                let path = SlashingModel::to_path(base_path, &slashing.id);
+
+                fs::remove_file(&path)
+                    .map(|_| slashing)
+                    .map_err(|err| format!("Failed to remove '{path}': {err}"))
+            })
+            .collect()
+    }
+}
+
+impl<'a, E: EthSpec> From<(&ConsolidatedBlock<E>, RwLockWriteGuard<'a, MetaCache>)>
+    for ConsolidatedSlashings<'a>
+{
+    fn from((block, meta_cache): (&ConsolidatedBlock<E>, RwLockWriteGuard<'a, MetaCache>)) -> Self {
+        let slashings = Vec::<SlashingModel>::from(block);
+
+        Self {
+            slashings,
+            ids: block.first_operation_ids().slashing..block.next_operation_ids().slashing,
+            meta_cache,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ops::Range, path::Path};
+
+    use parking_lot::RwLock;
+    use types::{
+        path::{Dirs, ToPath},
+        slashing::SlashingModel,
+        utils::MetaCache,
+    };
+
+    use super::ConsolidatedSlashings;
+    use crate::test_utils::temp_base_dir;
+
+    fn slashings(meta_cache: &RwLock<MetaCache>, ids: Range<u64>) -> ConsolidatedSlashings {
+        ConsolidatedSlashings {
+            slashings: ids
+                .clone()
+                .map(|id| SlashingModel {
+                    slot: id,
+                    kind: "proposer".to_string(),
+                    slashed_indices: vec![id],
+                    slashed_validators_count: 1,
+                    whistleblower: 0,
+                })
+                .collect(),
+            ids,
+            meta_cache: meta_cache.write(),
+        }
+    }
+
+    #[test]
+    fn test_save_and_rewind() {
+        let base_dir = temp_base_dir("rewind_slashings");
+        SlashingModel::create_dirs(&base_dir).unwrap();
+
+        let meta_cache = RwLock::new(MetaCache::new(base_dir.clone()));
+        let exists = |id: u64| Path::new(&SlashingModel::to_path(&base_dir, &id)).exists();
+
+        let saved = slashings(&meta_cache, 0..2).save(&base_dir).unwrap();
+
+        assert_eq!(saved.iter().map(|s| s.id).collect::<Vec<_>>(), vec![0, 1]);
+        assert_eq!(meta_cache.read().count::<SlashingModel>(), 2);
+
+        let removed = slashings(&meta_cache, 1..2).rewind(&base_dir).unwrap();
+
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].id, 1);
+        assert_eq!(removed[0].model.slashed_indices, vec![1]);
+        assert_eq!(meta_cache.read().count::<SlashingModel>(), 1);
+        assert!(exists(0) && !exists(1));
+    }
+}
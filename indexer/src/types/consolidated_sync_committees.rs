@@ -0,0 +1,28 @@
+use types::sync_committee::{SyncCommitteeModel, SyncCommitteeModelWithId};
+
+/// The current and next sync committees of a state, by validator index
+#[derive(Debug, Clone)]
+pub struct ConsolidatedSyncCommittees {
+    pub period: u64,
+    pub current: Vec<u64>,
+    pub next: Vec<u64>,
+}
+
+impl From<&ConsolidatedSyncCommittees> for Vec<SyncCommitteeModelWithId> {
+    fn from(value: &ConsolidatedSyncCommittees) -> Self {
+        vec![
+            SyncCommitteeModelWithId {
+                id: value.period,
+                model: SyncCommitteeModel {
+                    validators: value.current.clone(),
+                },
+            },
+            SyncCommitteeModelWithId {
+                id: value.period + 1,
+                model: SyncCommitteeModel {
+                    validators: value.next.clone(),
+                },
+            },
+        ]
+    }
+}
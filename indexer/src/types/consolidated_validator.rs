@@ -52,10 +52,33 @@ impl<'a, E: EthSpec> From<ConsolidatedValidator<'a, E>> for ValidatorModel {
                 far_future_epoch,
             ),
             status: value.status().to_string(),
+            slashing: None,
         }
     }
 }
 
+/// What a `ValidatorModel` is derived from, to find the validators which changed between epochs
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidatorSnapshot {
+    validator: Validator,
+    balance: u64,
+    status: ValidatorStatus,
+}
+
+impl ValidatorSnapshot {
+    pub fn new<E: EthSpec>(validator: &Validator, current_epoch: Epoch, balance: u64) -> Self {
+        Self {
+            validator: validator.clone(),
+            balance,
+            status: ConsolidatedValidator::<E>::new(validator, current_epoch, &balance).status(),
+        }
+    }
+
+    pub fn to_model<E: EthSpec>(&self, current_epoch: Epoch) -> ValidatorModel {
+        ConsolidatedValidator::<E>::new(&self.validator, current_epoch, &self.balance).into()
+    }
+}
+
 fn to_epoch_option(epoch: Epoch, far_future_epoch: Epoch) -> Option<u64> {
     if epoch == far_future_epoch {
         None
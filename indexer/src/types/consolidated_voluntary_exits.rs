@@ -0,0 +1,156 @@
+use std::{fs, io::ErrorKind, ops::Range};
+
+use lighthouse_types::EthSpec;
+use parking_lot::RwLockWriteGuard;
+use types::{
+    meta::Meta,
+    path::ToPath,
+    persistable::ResolvablePersistable,
+    utils::MetaCache,
+    voluntary_exit::{IndexedVoluntaryExitModel, IndexedVoluntaryExitModelWithId},
+};
+
+use super::consolidated_block::ConsolidatedBlock;
+
+/// The voluntary exits of a canonical block, numbered after the ones of the chain before it
+pub struct ConsolidatedVoluntaryExits<'a> {
+    voluntary_exits: Vec<IndexedVoluntaryExitModel>,
+    ids: Range<u64>,
+    meta_cache: RwLockWriteGuard<'a, MetaCache>,
+}
+
+impl<'a> ConsolidatedVoluntaryExits<'a> {
+    /// Saves the voluntary exits, the count then ending with the block
+    pub fn save(mut self, base_path: &str) -> Result<(), String> {
+        let first_id = self.ids.start;
+
+        let saved = self
+            .voluntary_exits
+            .into_iter()
+            .enumerate()
+            .map(|(i, voluntary_exit)| IndexedVoluntaryExitModelWithId {
+                id: first_id + i as u64,
+                model: voluntary_exit,
+            })
+            .try_fold(0_usize, |acc, voluntary_exit| {
+                voluntary_exit
+                    .save(base_path)
+                    .map(|_| acc + 1)
+                    .map_err(|err| (err, acc))
+            });
+
+        let (count, result) = match saved {
+            Ok(count) => (count, Ok(())),
+            Err((err, count)) => (count, Err(err)),
+        };
+
+        self.meta_cache
+            .entry::<IndexedVoluntaryExitModel>()
+            .update_count(first_id as usize + count)
+            .save::<IndexedVoluntaryExitModel>(base_path)?;
+
+        result
+    }
+
+    /// Removes the voluntary exits of a block displaced by a reorg, the new chain numbering
+    /// its own from the same id
+    pub fn rewind(mut self, base_path: &str) -> Result<(), String> {
+        let meta = self
+            .meta_cache
+            .loaded_entry::<IndexedVoluntaryExitModel>()
+            .or_insert_with(Meta::default);
+
+        meta.count = meta.count.min(self.ids.start as usize);
+        meta.save::<IndexedVoluntaryExitModel>(base_path)?;
+
+        self.ids.clone().try_for_each(|id| {
+            let path = IndexedVoluntaryExitModel::to_path(base_path, &id);
+
+            match fs::remove_file(&path) {
+                Err(err) if err.kind() != ErrorKind::NotFound => {
+                    Err(format!("Failed to remove '{path}': {err}"))
+                }
+                _ => Ok(()),
+            }
+        })
+    }
+}
+
+impl<'a, E: EthSpec> From<(&ConsolidatedBlock<E>, RwLockWriteGuard<'a, MetaCache>)>
+    for ConsolidatedVoluntaryExits<'a>
+{
+    fn from((block, meta_cache): (&ConsolidatedBlock<E>, RwLockWriteGuard<'a, MetaCache>)) -> Self {
+        let voluntary_exits = Vec::<IndexedVoluntaryExitModel>::from(block);
+
+        Self {
+            voluntary_exits,
+            ids: block.first_operation_ids().voluntary_exit
+                ..block.next_operation_ids().voluntary_exit,
+            meta_cache,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ops::Range, path::Path};
+
+    use parking_lot::RwLock;
+    use types::{
+        path::{Dirs, ToPath},
+        utils::MetaCache,
+        voluntary_exit::{IndexedVoluntaryExitModel, VoluntaryExitModel},
+    };
+
+    use super::ConsolidatedVoluntaryExits;
+    use crate::test_utils::temp_base_dir;
+
+    fn voluntary_exits(
+        meta_cache: &RwLock<MetaCache>,
+        ids: Range<u64>,
+    ) -> ConsolidatedVoluntaryExits {
+        ConsolidatedVoluntaryExits {
+            voluntary_exits: ids
+                .clone()
+                .map(|id| IndexedVoluntaryExitModel {
+                    slot: id,
+                    voluntary_exit: VoluntaryExitModel {
+                        validator_index: id,
+                        epoch: 0,
+                        signature: String::new(),
+                    },
+                })
+                .collect(),
+            ids,
+            meta_cache: meta_cache.write(),
+        }
+    }
+
+    #[test]
+    fn test_save_and_rewind() {
+        let base_dir = temp_base_dir("rewind_voluntary_exits");
+        IndexedVoluntaryExitModel::create_dirs(&base_dir).unwrap();
+
+        let meta_cache = RwLock::new(MetaCache::new(base_dir.clone()));
+        let exists =
+            |id: u64| Path::new(&IndexedVoluntaryExitModel::to_path(&base_dir, &id)).exists();
+
+        voluntary_exits(&meta_cache, 0..2).save(&base_dir).unwrap();
+        voluntary_exits(&meta_cache, 2..3).save(&base_dir).unwrap();
+
+        assert_eq!(meta_cache.read().count::<IndexedVoluntaryExitModel>(), 3);
+
+        // The displaced block numbered its voluntary exits from 1, the new chain does the same
+        voluntary_exits(&meta_cache, 1..3)
+            .rewind(&base_dir)
+            .unwrap();
+
+        assert_eq!(meta_cache.read().count::<IndexedVoluntaryExitModel>(), 1);
+        assert!(exists(0) && !exists(1) && !exists(2));
+
+        voluntary_exits(&meta_cache, 1..2).save(&base_dir).unwrap();
+
+        assert_eq!(meta_cache.read().count::<IndexedVoluntaryExitModel>(), 2);
+        assert!(exists(1));
+    }
+}
@@ -1,6 +1,10 @@
+pub mod attestation_performances;
 pub mod block_state;
 pub mod consolidated_block;
 pub mod consolidated_deposits;
 pub mod consolidated_epoch;
 pub mod consolidated_execution_layer_deposit;
+pub mod consolidated_slashings;
+pub mod consolidated_sync_committees;
 pub mod consolidated_validator;
+pub mod consolidated_voluntary_exits;
@@ -1,4 +1,4 @@
-use lighthouse_types::{EthSpec, Hash256};
+use lighthouse_types::{Epoch, EthSpec, Hash256};
 use types::utils::RequestAttempts;
 
 use crate::types::{
@@ -9,9 +9,11 @@ use crate::types::{
 #[derive(Debug)]
 pub enum Work<E: EthSpec> {
     PersistIndexingState(),
+    PersistSnapshot,
     PersistDepositFromExecutionLayer(ConsolidatedExecutionLayerDeposit),
     PersistBlock(ConsolidatedBlock<E>),
     PersistEpoch(ConsolidatedEpoch<E>),
+    PersistOrphanInEpoch(Epoch),
     PersistBlockRequest(Hash256, RequestAttempts),
     PersistAllBlockRequests,
     PersistAllGoodPeers,
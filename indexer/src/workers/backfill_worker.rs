@@ -0,0 +1,755 @@
+use std::{collections::HashSet, fs, io::ErrorKind, mem, sync::Arc};
+
+use lighthouse_network::{NetworkEvent as ConsensusNetworkEvent, PeerId, Response};
+use lighthouse_types::{
+    BeaconBlock, BeaconState, ChainSpec, EthSpec, Hash256, Signature, SignedBeaconBlock, Slot,
+};
+use serde::{Deserialize, Serialize};
+use ssz::Encode;
+use task_executor::TaskExecutor;
+use tokio::{
+    select,
+    sync::{
+        mpsc::{UnboundedReceiver, UnboundedSender},
+        watch::Receiver,
+    },
+};
+use tracing::{debug, error, info, warn};
+use types::{
+    block::{BlockExtendedModel, BlockModel},
+    path::{FromPath, ToPath},
+    persistable::{MsgPackDeserializable, MsgPackSerializable},
+    utils::{ModelCache, PersistableCache},
+    vote::VoteModel,
+    DeserializeOwned,
+};
+
+use crate::{
+    beacon_chain::beacon_context::BeaconContext,
+    db::{IndexingState, Stores},
+    network::{ConsensusNetworkCommand, RequestId},
+    types::{attestation_performances::AttestationPerformances, block_state::BlockState},
+    workers::{persist_block_worker::persist_block_models, spawn_persist_replayed_epoch_worker},
+};
+
+const BATCH_SIZE: u64 = 32;
+
+/// The largest range requested when peers keep answering with no block
+const MAX_BATCH_SIZE: u64 = 1024;
+
+/// How many times every peer may answer the largest range with no block before the backfill
+/// gives up until the next restart
+const MAX_EMPTY_ROUNDS: u32 = 8;
+
+/// How many slots are replayed between two saves of the replay progress
+const REPLAY_SEGMENT_SLOTS: u64 = 1024;
+
+/// Walks backwards from the anchor the indexing started from, downloading the blocks down to genesis.
+/// Once the walk is complete, the state is replayed from genesis to persist the blocks and epochs
+/// before the anchor. The replay needs the state before each block, so it can't start before the
+/// walk verified the chain down to genesis, but it persists every segment as soon as it's replayed.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_backfill_worker<E: EthSpec + Serialize + DeserializeOwned>(
+    base_dir: String,
+    beacon_context: Arc<BeaconContext<E>>,
+    anchor_block: Option<Arc<SignedBeaconBlock<E>>>,
+    consensus_event_recv: UnboundedReceiver<ConsensusNetworkEvent<RequestId, E>>,
+    consensus_command_send: UnboundedSender<ConsensusNetworkCommand>,
+    stores: Arc<Stores<E>>,
+    shutdown_trigger: Receiver<()>,
+    executor: &TaskExecutor,
+) {
+    let not_indexed = stores
+        .meta_cache_mut()
+        .get::<BlockModel>()
+        .and_then(|meta| meta.not_indexed());
+
+    if not_indexed.is_none() {
+        info!("Nothing to backfill");
+        return;
+    }
+
+    let genesis_block = match genesis_block(&beacon_context.genesis_state, &beacon_context.spec) {
+        Ok(genesis_block) => Arc::new(genesis_block),
+        Err(err) => {
+            error!("Unable to start the backfill: {err}");
+            return;
+        }
+    };
+
+    let mut state = match BackfillState::from_path(&base_dir, &()) {
+        Ok(state) => state,
+        Err(_) => match anchor_block {
+            Some(anchor_block) => match BackfillState::init(&base_dir, &anchor_block) {
+                Ok(state) => state,
+                Err(err) => {
+                    error!("Unable to start the backfill: {err}");
+                    return;
+                }
+            },
+            None => {
+                warn!("The backfill requires the checkpoint the indexing started from");
+                return;
+            }
+        },
+    };
+
+    if let Err(err) = state
+        .reach_genesis(&base_dir, &genesis_block)
+        .and_then(|_| state.save(&base_dir))
+    {
+        error!("Unable to start the backfill: {err}");
+        return;
+    }
+
+    info!(anchor_slot = %state.anchor_slot, remaining = %state.remaining_end, "Starting backfill");
+
+    BackfillWorker {
+        base_dir,
+        beacon_context,
+        consensus_event_recv,
+        consensus_command_send,
+        stores,
+        state,
+        genesis_block,
+        connected_peers: HashSet::new(),
+        empty_responses: HashSet::new(),
+        empty_rounds: 0,
+        active_request: None,
+        batch_size: BATCH_SIZE,
+        batch: vec![],
+    }
+    .spawn(shutdown_trigger, executor);
+}
+
+/// Progress of the backward walk, persisted so that it resumes after a restart
+#[derive(Serialize, Deserialize, Debug)]
+struct BackfillState {
+    anchor_slot: Slot,
+    /// Every block from this slot up to the anchor has been downloaded
+    remaining_end: Slot,
+    /// Root of the next block expected while walking backwards
+    expected_root: Hash256,
+}
+
+impl BackfillState {
+    fn init<E: EthSpec>(
+        base_dir: &str,
+        anchor_block: &SignedBeaconBlock<E>,
+    ) -> Result<Self, String> {
+        let dir = backfill_dir(base_dir);
+
+        fs::create_dir_all(&dir).map_err(|err| format!("Failed to create '{dir}': {err}"))?;
+
+        save_block(base_dir, anchor_block)?;
+
+        let state = Self {
+            anchor_slot: anchor_block.slot(),
+            remaining_end: anchor_block.slot(),
+            expected_root: anchor_block.parent_root(),
+        };
+
+        state.save(base_dir)?;
+
+        Ok(state)
+    }
+
+    fn is_complete(&self) -> bool {
+        self.remaining_end == 0
+    }
+
+    /// The start slot and the count of the next range request
+    fn next_range(&self, batch_size: u64) -> (Slot, u64) {
+        let count = batch_size.min(self.remaining_end.as_u64());
+
+        (self.remaining_end - count, count)
+    }
+
+    /// The blocks of a batch answering the range starting at `start_slot` which chain up to the
+    /// oldest downloaded block, from the newest to the oldest. Fails if a block doesn't chain up.
+    fn chain_batch<E: EthSpec>(
+        &self,
+        batch: &[Arc<SignedBeaconBlock<E>>],
+        start_slot: Slot,
+    ) -> Result<Vec<Arc<SignedBeaconBlock<E>>>, String> {
+        let mut expected_root = self.expected_root;
+
+        batch
+            .iter()
+            .rev()
+            .filter(|block| block.slot() >= start_slot && block.slot() < self.remaining_end)
+            .map(|block| {
+                let root = block.canonical_root();
+
+                if root != expected_root {
+                    return Err(format!(
+                        "The backfilled block at {} doesn't chain up ({root:?} != {expected_root:?})",
+                        block.slot()
+                    ));
+                }
+
+                expected_root = block.parent_root();
+
+                Ok(block.clone())
+            })
+            .collect()
+    }
+
+    /// Moves the walk below `oldest`, the oldest block which chained up. Slots are only walked
+    /// past once a block below them has chained up, so a batch missing blocks is never skipped.
+    fn advance<E: EthSpec>(&mut self, oldest: &SignedBeaconBlock<E>) {
+        self.remaining_end = oldest.slot();
+        self.expected_root = oldest.parent_root();
+    }
+
+    /// Completes the walk with the genesis block once the oldest downloaded block chains up to
+    /// it, as peers may not serve the genesis slot
+    fn reach_genesis<E: EthSpec>(
+        &mut self,
+        base_dir: &str,
+        genesis_block: &SignedBeaconBlock<E>,
+    ) -> Result<(), String> {
+        if self.is_complete() || self.expected_root != genesis_block.canonical_root() {
+            return Ok(());
+        }
+
+        save_block(base_dir, genesis_block)?;
+        self.advance(genesis_block);
+
+        Ok(())
+    }
+
+    fn save(&self, base_dir: &str) -> Result<(), String> {
+        self.serialize_to_file(&Self::to_path(base_dir, &()))
+    }
+}
+
+impl ToPath for BackfillState {
+    type Id = ();
+
+    fn to_path(base_dir: &str, _: &Self::Id) -> String {
+        format!("{}/state.msg", backfill_dir(base_dir))
+    }
+}
+
+impl MsgPackSerializable for BackfillState {}
+
+struct BackfillWorker<E: EthSpec> {
+    base_dir: String,
+    beacon_context: Arc<BeaconContext<E>>,
+    consensus_event_recv: UnboundedReceiver<ConsensusNetworkEvent<RequestId, E>>,
+    consensus_command_send: UnboundedSender<ConsensusNetworkCommand>,
+    stores: Arc<Stores<E>>,
+    state: BackfillState,
+    genesis_block: Arc<SignedBeaconBlock<E>>,
+    connected_peers: HashSet<PeerId>,
+    /// The peers which answered the current range with no block
+    empty_responses: HashSet<PeerId>,
+    /// How many times every peer answered the largest range with no block
+    empty_rounds: u32,
+    active_request: Option<PeerId>,
+    batch_size: u64,
+    batch: Vec<Arc<SignedBeaconBlock<E>>>,
+}
+
+impl<E: EthSpec + Serialize + DeserializeOwned> BackfillWorker<E> {
+    pub fn spawn(mut self, mut shutdown_trigger: Receiver<()>, executor: &TaskExecutor) {
+        let task_executor = executor.clone();
+
+        executor.spawn(
+            async move {
+                loop {
+                    if self.state.is_complete() {
+                        if let Err(err) = self.replay(shutdown_trigger, &task_executor).await {
+                            error!("{err}");
+                        }
+                        return;
+                    }
+
+                    select! {
+                        Some(event) = self.consensus_event_recv.recv() => {
+                            if let Err(err) = self.handle_consensus_event(event) {
+                                error!("{err}");
+                                info!("Shutting down backfill worker");
+                                return;
+                            }
+                        },
+
+                        _ = shutdown_trigger.changed() => {
+                            info!("Shutting down backfill worker...");
+                            return;
+                        }
+                    }
+                }
+            },
+            "backfill worker",
+        );
+    }
+
+    fn handle_consensus_event(
+        &mut self,
+        event: ConsensusNetworkEvent<RequestId, E>,
+    ) -> Result<(), String> {
+        match event {
+            ConsensusNetworkEvent::PeerConnectedOutgoing(peer_id) => {
+                self.connected_peers.insert(peer_id);
+
+                if self.active_request.is_none() {
+                    self.send_range_request()?;
+                }
+            }
+
+            ConsensusNetworkEvent::PeerDisconnected(peer_id)
+            | ConsensusNetworkEvent::RPCFailed {
+                id: RequestId::Backfill,
+                peer_id,
+            } => {
+                self.connected_peers.remove(&peer_id);
+
+                if self.active_request == Some(peer_id) {
+                    debug!(to = %peer_id, "Backfill range request cancelled");
+                    self.batch.clear();
+                    self.active_request = None;
+                    self.send_range_request()?;
+                }
+            }
+
+            ConsensusNetworkEvent::ResponseReceived {
+                id: RequestId::Backfill,
+                response: Response::BlocksByRange(Some(block)),
+                ..
+            } => self.batch.push(block),
+
+            ConsensusNetworkEvent::ResponseReceived {
+                id: RequestId::Backfill,
+                response: Response::BlocksByRange(None),
+                peer_id,
+            } => {
+                self.active_request = None;
+
+                let batch = mem::take(&mut self.batch);
+                let (start_slot, _) = self.state.next_range(self.batch_size);
+
+                match self.state.chain_batch(&batch, start_slot) {
+                    Ok(blocks) if blocks.is_empty() => {
+                        // The peer may have pruned the range, which is retried with another one
+                        debug!(peer = %peer_id, %start_slot, "Empty backfill batch");
+                        self.empty_responses.insert(peer_id);
+                    }
+                    Ok(blocks) => self.store_blocks(&blocks)?,
+                    Err(err) => {
+                        warn!(peer = %peer_id, "{err}");
+
+                        self.connected_peers.remove(&peer_id);
+                        self.consensus_command_send
+                            .send(ConsensusNetworkCommand::ReportPeer(
+                                peer_id,
+                                "Invalid backfill batch",
+                            ))
+                            .map_err(|_| "Unable to report peer".to_string())?;
+                    }
+                }
+
+                if !self.state.is_complete() {
+                    self.send_range_request()?;
+                }
+            }
+
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn send_range_request(&mut self) -> Result<(), String> {
+        if self.connected_peers.is_empty() {
+            debug!("No peer available for a new backfill range request");
+            return Ok(());
+        }
+
+        if self.connected_peers.is_subset(&self.empty_responses) {
+            // Either every peer pruned the range or its slots have all been missed
+            if self.batch_size == MAX_BATCH_SIZE {
+                self.empty_rounds += 1;
+
+                if self.empty_rounds >= MAX_EMPTY_ROUNDS {
+                    return Err(format!(
+                        "No peer serves the blocks before slot {}, the backfill resumes on restart",
+                        self.state.remaining_end
+                    ));
+                }
+            }
+
+            self.batch_size = (self.batch_size * 2).min(MAX_BATCH_SIZE);
+            self.empty_responses.clear();
+
+            debug!(count = self.batch_size, "Widening the backfill range");
+        }
+
+        let peer_id = match self
+            .connected_peers
+            .iter()
+            .find(|peer_id| !self.empty_responses.contains(peer_id))
+        {
+            Some(peer_id) => *peer_id,
+            None => return Ok(()),
+        };
+
+        let (start_slot, count) = self.state.next_range(self.batch_size);
+
+        self.active_request = Some(peer_id);
+
+        self.consensus_command_send
+            .send(ConsensusNetworkCommand::SendBackfillRangeRequest {
+                peer_id,
+                start_slot: start_slot.as_u64(),
+                count,
+            })
+            .map_err(|_| "Unable to send backfill range request".to_string())
+    }
+
+    /// Stores the blocks which chained up, from the newest to the oldest, then moves the walk below them
+    fn store_blocks(&mut self, blocks: &[Arc<SignedBeaconBlock<E>>]) -> Result<(), String> {
+        let oldest = match blocks.last() {
+            Some(oldest) => oldest,
+            None => return Ok(()),
+        };
+
+        blocks
+            .iter()
+            .try_for_each(|block| save_block(&self.base_dir, block))?;
+
+        self.state.advance(oldest);
+        self.state
+            .reach_genesis(&self.base_dir, &self.genesis_block)?;
+        self.state.save(&self.base_dir)?;
+
+        self.batch_size = BATCH_SIZE;
+        self.empty_responses.clear();
+        self.empty_rounds = 0;
+
+        info!(slot = %self.state.remaining_end, "Backfilled");
+
+        Ok(())
+    }
+
+    /// Replays the backfilled blocks segment by segment, each one in its own blocking task.
+    /// The replay stops between two segments when shutting down, and resumes from there.
+    async fn replay(
+        self,
+        shutdown_trigger: Receiver<()>,
+        executor: &TaskExecutor,
+    ) -> Result<(), String> {
+        let anchor_slot = self.state.anchor_slot;
+        let mut replay = Replay::load(&self.base_dir, &self.beacon_context);
+
+        info!(
+            from = %replay.next_slot(),
+            %anchor_slot,
+            "Replaying backfilled blocks, without their deposits, voluntary exits and slashings"
+        );
+
+        while replay.next_slot() <= anchor_slot {
+            if shutdown_trigger.has_changed().unwrap_or(true) {
+                info!(slot = %replay.next_slot(), "The backfill replay has been interrupted");
+                return Ok(());
+            }
+
+            let base_dir = self.base_dir.clone();
+            let stores = self.stores.clone();
+            let task_executor = executor.clone();
+
+            let segment = executor
+                .spawn_blocking_handle(
+                    move || {
+                        let result =
+                            replay.replay_segment(&base_dir, anchor_slot, &stores, &task_executor);
+
+                        (replay, result)
+                    },
+                    "backfill replay segment",
+                )
+                .ok_or("The backfill replay can't be spawned while shutting down")?;
+
+            let (replayed, result) = segment
+                .await
+                .map_err(|err| format!("The backfill replay segment failed: {err}"))?;
+
+            result?;
+            replay = replayed;
+        }
+
+        let dir = backfill_dir(&self.base_dir);
+
+        fs::remove_dir_all(&dir).map_err(|err| format!("Failed to remove '{dir}': {err}"))?;
+
+        info!("Backfill completed");
+
+        Ok(())
+    }
+}
+
+/// The state replayed from genesis to persist everything before the anchor, along with the caches
+/// of the persisted blocks. Deposits, voluntary exits and slashings aren't persisted, as their
+/// global ids follow the indexing order and the ones after the anchor have already been numbered.
+struct Replay<E: EthSpec> {
+    indexing_state: IndexingState<E>,
+    spec: ChainSpec,
+    extended_blocks_cache: ModelCache<Option<BlockExtendedModel>>,
+    votes_cache: PersistableCache<Vec<VoteModel>>,
+    attestation_performances: AttestationPerformances,
+}
+
+impl<E: EthSpec + Serialize + DeserializeOwned> Replay<E> {
+    /// Resumes the replay saved after the latest replayed segment, or starts it from genesis.
+    /// The replayed chain is never reorged, so no state is retained.
+    fn load(base_dir: &str, beacon_context: &BeaconContext<E>) -> Self {
+        let indexing_state = IndexingState::deserialize_from_file(&replay_state_path(base_dir))
+            .map(|indexing_state| indexing_state.with_spec(beacon_context.spec.clone()))
+            .unwrap_or_else(|_| {
+                IndexingState::new(
+                    beacon_context.genesis_state.clone(),
+                    beacon_context
+                        .eth2_network_config
+                        .deposit_contract_deploy_block,
+                    beacon_context.spec.clone(),
+                )
+            })
+            .with_reorg_depth(0);
+
+        Self {
+            indexing_state,
+            spec: beacon_context.spec.clone(),
+            extended_blocks_cache: ModelCache::new(base_dir.to_string()),
+            votes_cache: PersistableCache::new(base_dir.to_string()),
+            attestation_performances: AttestationPerformances::default(),
+        }
+    }
+
+    fn next_slot(&self) -> Slot {
+        self.indexing_state
+            .latest_slot()
+            .map_or(Slot::new(0), |slot| slot + 1)
+    }
+
+    /// Replays and persists the next `REPLAY_SEGMENT_SLOTS` slots, up to the anchor included,
+    /// then saves the replay state and removes the replayed blocks.
+    fn replay_segment(
+        &mut self,
+        base_dir: &str,
+        anchor_slot: Slot,
+        stores: &Arc<Stores<E>>,
+        executor: &TaskExecutor,
+    ) -> Result<(), String> {
+        let start_slot = self.next_slot();
+        let end_slot = (start_slot + REPLAY_SEGMENT_SLOTS).min(anchor_slot + 1);
+
+        for slot in (start_slot.as_u64()..end_slot.as_u64()).map(Slot::new) {
+            let block = match read_block(base_dir, slot, &self.spec)? {
+                Some(block) => BlockState::Proposed(block),
+                None => BlockState::Missed(slot),
+            };
+
+            let (block, epoch) = self.indexing_state.process_block(block, stores.clock())?;
+
+            // The anchor block has already been persisted by the index worker
+            if slot < anchor_slot {
+                persist_block_models(
+                    base_dir,
+                    &block,
+                    stores,
+                    &mut self.extended_blocks_cache,
+                    &mut self.votes_cache,
+                    &mut self.attestation_performances,
+                )?;
+            }
+
+            if let Some(epoch) = epoch {
+                spawn_persist_replayed_epoch_worker(base_dir.to_string(), epoch, stores, executor);
+            }
+        }
+
+        save_replay_state(base_dir, &self.indexing_state)?;
+        remove_blocks(base_dir, start_slot, end_slot.min(anchor_slot))?;
+
+        info!(slot = %(end_slot - 1), "Replayed backfilled blocks");
+
+        Ok(())
+    }
+}
+
+/// The genesis block, built from the genesis state like the beacon node does
+fn genesis_block<E: EthSpec>(
+    genesis_state: &BeaconState<E>,
+    spec: &ChainSpec,
+) -> Result<SignedBeaconBlock<E>, String> {
+    let mut genesis_state = genesis_state.clone();
+    let mut block = BeaconBlock::empty(spec);
+
+    *block.state_root_mut() = genesis_state
+        .update_tree_hash_cache()
+        .map_err(|err| format!("Can't compute the genesis state root: {err:?}"))?;
+
+    Ok(SignedBeaconBlock::from_block(block, Signature::empty()))
+}
+
+fn backfill_dir(base_dir: &str) -> String {
+    format!("{base_dir}/backfill")
+}
+
+fn replay_state_path(base_dir: &str) -> String {
+    format!("{}/replay.msg", backfill_dir(base_dir))
+}
+
+/// The replay state is written next to its final path first, so that a crash never leaves a truncated one.
+fn save_replay_state<E: EthSpec + Serialize>(
+    base_dir: &str,
+    indexing_state: &IndexingState<E>,
+) -> Result<(), String> {
+    let path = replay_state_path(base_dir);
+    let tmp_path = format!("{path}.tmp");
+
+    indexing_state.serialize_to_file(&tmp_path)?;
+
+    fs::rename(&tmp_path, &path).map_err(|err| format!("Can't rename '{tmp_path}': {err}"))
+}
+
+fn block_path(base_dir: &str, slot: Slot) -> String {
+    format!("{}/{slot}.ssz", backfill_dir(base_dir))
+}
+
+fn save_block<E: EthSpec>(base_dir: &str, block: &SignedBeaconBlock<E>) -> Result<(), String> {
+    let path = block_path(base_dir, block.slot());
+
+    fs::write(&path, block.as_ssz_bytes()).map_err(|err| format!("Can't write '{path}': {err}"))
+}
+
+fn read_block<E: EthSpec>(
+    base_dir: &str,
+    slot: Slot,
+    spec: &ChainSpec,
+) -> Result<Option<Arc<SignedBeaconBlock<E>>>, String> {
+    let path = block_path(base_dir, slot);
+
+    match fs::read(&path) {
+        Ok(bytes) => SignedBeaconBlock::from_ssz_bytes(&bytes, spec)
+            .map(|block| Some(Arc::new(block)))
+            .map_err(|err| format!("Can't decode '{path}': {err:?}")),
+        // Only the slots which have a block have a file
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(format!("Can't read '{path}': {err}")),
+    }
+}
+
+/// Removes the replayed blocks of `start_slot..end_slot`
+fn remove_blocks(base_dir: &str, start_slot: Slot, end_slot: Slot) -> Result<(), String> {
+    (start_slot.as_u64()..end_slot.as_u64())
+        .map(|slot| block_path(base_dir, Slot::new(slot)))
+        .try_for_each(|path| match fs::remove_file(&path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => {
+                Err(format!("Failed to remove '{path}': {err}"))
+            }
+            _ => Ok(()),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, sync::Arc};
+
+    use lighthouse_types::{MainnetEthSpec, SignedBeaconBlock, Slot};
+
+    use super::{backfill_dir, genesis_block, read_block, BackfillState, BATCH_SIZE};
+    use crate::test_utils::{temp_base_dir, BeaconChainHarness};
+
+    async fn make_chain(count: u64) -> Vec<Arc<SignedBeaconBlock<MainnetEthSpec>>> {
+        let mut harness = BeaconChainHarness::new();
+        let mut blocks = vec![];
+
+        for slot in 1..=count {
+            blocks.push(Arc::new(harness.make_block(slot).await));
+        }
+
+        blocks
+    }
+
+    fn state_from(anchor_block: &SignedBeaconBlock<MainnetEthSpec>) -> BackfillState {
+        BackfillState {
+            anchor_slot: anchor_block.slot(),
+            remaining_end: anchor_block.slot(),
+            expected_root: anchor_block.parent_root(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_empty_batch_keeps_the_range() {
+        let blocks = make_chain(4).await;
+        let state = state_from(&blocks[3]);
+        let (start_slot, count) = state.next_range(BATCH_SIZE);
+
+        let chained = state
+            .chain_batch::<MainnetEthSpec>(&[], start_slot)
+            .unwrap();
+
+        assert!(chained.is_empty());
+        assert_eq!(state.next_range(BATCH_SIZE), (start_slot, count));
+        assert_eq!(state.expected_root, blocks[3].parent_root());
+        assert!(!state.is_complete());
+    }
+
+    #[tokio::test]
+    async fn test_batch_chaining_up() {
+        let blocks = make_chain(4).await;
+        let mut state = state_from(&blocks[3]);
+        let (start_slot, _) = state.next_range(BATCH_SIZE);
+
+        // The anchor block is out of the range and ignored
+        let chained = state.chain_batch(&blocks, start_slot).unwrap();
+
+        assert_eq!(
+            chained.iter().map(|block| block.slot()).collect::<Vec<_>>(),
+            vec![Slot::new(3), Slot::new(2), Slot::new(1)]
+        );
+
+        state.advance(chained.last().unwrap());
+
+        assert_eq!(state.remaining_end, Slot::new(1));
+        assert_eq!(state.expected_root, blocks[0].parent_root());
+        assert_eq!(state.next_range(BATCH_SIZE), (Slot::new(0), 1));
+    }
+
+    #[tokio::test]
+    async fn test_batch_not_chaining_up() {
+        let blocks = make_chain(4).await;
+        let state = state_from(&blocks[3]);
+        let (start_slot, _) = state.next_range(BATCH_SIZE);
+
+        let batch = vec![blocks[0].clone(), blocks[2].clone()];
+
+        assert!(state.chain_batch(&batch, start_slot).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_walk_reaching_genesis() {
+        let mut harness = BeaconChainHarness::new();
+        let genesis_block = genesis_block(&harness.state(), &harness.spec()).unwrap();
+        let anchor_block = harness.make_block(2).await;
+
+        let base_dir = temp_base_dir("backfill_genesis");
+        fs::create_dir_all(backfill_dir(&base_dir)).unwrap();
+
+        // The slot 1 has been missed, so the anchor chains up to the genesis block
+        let mut state = state_from(&anchor_block);
+
+        assert_eq!(state.expected_root, genesis_block.canonical_root());
+
+        state.reach_genesis(&base_dir, &genesis_block).unwrap();
+
+        assert!(state.is_complete());
+        assert!(
+            read_block::<MainnetEthSpec>(&base_dir, Slot::new(0), &harness.spec())
+                .unwrap()
+                .is_some()
+        );
+    }
+}
@@ -1,27 +1,27 @@
 use std::{
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     iter::{once, zip},
     sync::Arc,
 };
 
 use eth1::DepositLog;
 use itertools::Itertools;
-use lighthouse_network::{NetworkEvent as ConsensusNetworkEvent, PeerId, Response};
-use lighthouse_types::{EthSpec, SignedBeaconBlock, Slot};
+use lighthouse_network::{NetworkEvent as ConsensusNetworkEvent, PeerId, PubsubMessage, Response};
+use lighthouse_types::{EthSpec, Hash256, SignedBeaconBlock, Slot};
 use parking_lot::RwLock;
 use task_executor::TaskExecutor;
 use tokio::{
     select,
     sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
 };
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 use types::block_root::{BlockRootModel, BlockRootModelWithId};
 
 use crate::{
     db::Stores,
     network::{ConsensusNetworkCommand, ExecutionNetworkCommand, ExecutionNetworkEvent, RequestId},
     types::{
-        block_state::BlockState,
+        block_state::BlockState, consolidated_block::ConsolidatedBlock,
         consolidated_execution_layer_deposit::ConsolidatedExecutionLayerDeposit,
     },
     work::Work,
@@ -57,6 +57,8 @@ struct IndexWorker<E: EthSpec> {
     consensus_command_send: UnboundedSender<ConsensusNetworkCommand>,
     work_send: UnboundedSender<Work<E>>,
     block_range_requests: RwLock<BlockRangeRequests<E>>,
+    /// Blocks of already processed slots competing with the indexed ones, by root
+    competing_blocks: RwLock<HashMap<Hash256, Arc<SignedBeaconBlock<E>>>>,
     stores: Arc<Stores<E>>,
 }
 
@@ -76,6 +78,7 @@ impl<E: EthSpec> IndexWorker<E> {
             consensus_command_send,
             work_send,
             block_range_requests: RwLock::new(BlockRangeRequests::default()),
+            competing_blocks: RwLock::new(HashMap::new()),
             stores,
         }
     }
@@ -116,7 +119,11 @@ impl<E: EthSpec> IndexWorker<E> {
     ) -> Result<(), IndexError> {
         match event {
             ConsensusNetworkEvent::PeerConnectedOutgoing(peer_id) => {
-                if !self.block_range_requests.read().is_requesting() {
+                let block_range_requests = self.block_range_requests.read();
+
+                if !block_range_requests.is_requesting()
+                    && !block_range_requests.is_following_head()
+                {
                     self.send_range_request(Some(peer_id))?;
                 }
 
@@ -180,61 +187,18 @@ impl<E: EthSpec> IndexWorker<E> {
                 let mut block_range_requests = self.block_range_requests.write();
 
                 if let Some(block) = block {
-                    self.stores
-                        .block_roots_cache()
-                        .write()
-                        .put(BlockRootModelWithId {
-                            id: format!("{:?}", block.canonical_root()),
-                            model: BlockRootModel {
-                                slot: block.slot().as_u64(),
-                            },
-                        });
-
                     let block = block_range_requests.next_or(block);
 
-                    if Some(block.slot()) > self.stores.indexing_state().latest_slot() {
-                        new_blocks(block.clone(), &self.stores).try_for_each(|block| match self
-                            .stores
-                            .indexing_state_mut()
-                            .process_block(block)
-                        {
-                            Ok((block, epoch)) => {
-                                self.work_send
-                                    .send(Work::PersistBlock(block))
-                                    .map_err(|_| IndexError::SendMessage)?;
+                    self.process_new_block(block)?;
+                } else if self.is_caught_up() {
+                    block_range_requests.request_terminated(&peer_id);
 
-                                if let Some(epoch) = epoch {
-                                    self.work_send
-                                        .send(Work::PersistEpoch(epoch))
-                                        .map_err(|_| IndexError::SendMessage)?;
-                                }
-                                Ok(())
-                            }
-                            Err(err) => Err(IndexError::BlockProcessing(err)),
-                        })?;
-
-                        block
-                            .message()
-                            .body()
-                            .attestations()
-                            .iter()
-                            .map(|a| (a.data.slot, a.data.beacon_block_root))
-                            .dedup()
-                            .filter(|(_, r)| {
-                                !self.stores
-                                    .block_roots_cache()
-                                    .write()
-                                    .contains(&format!("{r:?}"))
-                            })
-                            .try_for_each(|(slot, root)| {
-                                info!(%slot, %root, "Unknown root while processing block {}", block.slot());
-                                self.stores.block_by_root_requests_mut().add(slot, root);
-
-                                self
-                                    .consensus_command_send
-                                    .send(ConsensusNetworkCommand::SendBlockByRootRequest { peer_id: None, root })
-                                    .map_err(|_| IndexError::SendMessage)
-                            })?;
+                    if block_range_requests.follow_head() {
+                        info!("Caught up with the head, following gossip blocks");
+
+                        self.consensus_command_send
+                            .send(ConsensusNetworkCommand::SubscribeToBlocks)
+                            .map_err(|_| IndexError::SendMessage)?;
                     }
                 } else if block_range_requests.request_terminated(&peer_id) {
                     // There is no more active range requests
@@ -246,6 +210,23 @@ impl<E: EthSpec> IndexWorker<E> {
                 }
             }
 
+            ConsensusNetworkEvent::PubsubMessage {
+                message: PubsubMessage::BeaconBlock(block),
+                ..
+            } => {
+                match self.process_new_block(block) {
+                    Err(IndexError::BlockProcessing(err)) => {
+                        // Blocks have been missed, catch up with a range request
+                        debug!("{err}");
+
+                        if !self.block_range_requests.read().is_requesting() {
+                            self.send_range_request(None)?;
+                        }
+                    }
+                    result => result?,
+                }
+            }
+
             ConsensusNetworkEvent::ResponseReceived {
                 peer_id,
                 id: RequestId::Block(root),
@@ -265,6 +246,11 @@ impl<E: EthSpec> IndexWorker<E> {
                                 self.work_send
                                     .send(Work::PersistBlockRequest(root, attempt.clone()))
                                     .map_err(|_| IndexError::SendMessage)?;
+
+                                // A block can't be attested before its slot
+                                if attempt.possible_slots.iter().any(|s| slot <= *s) {
+                                    self.persist_orphaned_block(block)?;
+                                }
                             }
 
                             //consensus_network.peer_db_mut().add_good_peer(peer_id);
@@ -312,6 +298,226 @@ impl<E: EthSpec> IndexWorker<E> {
         Ok(())
     }
 
+    /// Indexes a block received either from a range request or from gossip
+    fn process_new_block(&self, block: Arc<SignedBeaconBlock<E>>) -> Result<(), IndexError> {
+        self.stores
+            .block_roots_cache()
+            .write()
+            .put(BlockRootModelWithId {
+                id: format!("{:?}", block.canonical_root()),
+                model: BlockRootModel {
+                    slot: block.slot().as_u64(),
+                },
+            });
+
+        // The head switches to a competing block once a later block builds on it
+        let competing_parent = self.competing_blocks.write().remove(&block.parent_root());
+
+        if let Some(competing_parent) = competing_parent {
+            self.index_block(competing_parent, true)?;
+        }
+
+        self.index_block(block, false)
+    }
+
+    /// Processes the block and the slots missed before it, unless it competes with an already
+    /// processed block and `switch_head` isn't set
+    fn index_block(
+        &self,
+        block: Arc<SignedBeaconBlock<E>>,
+        switch_head: bool,
+    ) -> Result<(), IndexError> {
+        let displaced_blocks = self.handle_reorg(&block, switch_head)?;
+
+        if Some(block.slot()) > self.stores.indexing_state().latest_slot() {
+            new_blocks(block.clone(), &self.stores).try_for_each(|block| {
+                match self
+                    .stores
+                    .indexing_state_mut()
+                    .process_block(block, self.stores.clock())
+                {
+                    Ok((block, epoch)) => {
+                        self.work_send
+                            .send(Work::PersistBlock(block))
+                            .map_err(|_| IndexError::SendMessage)?;
+
+                        if let Some(epoch) = epoch {
+                            self.work_send
+                                .send(Work::PersistEpoch(epoch))
+                                .map_err(|_| IndexError::SendMessage)?;
+
+                            self.work_send
+                                .send(Work::PersistSnapshot)
+                                .map_err(|_| IndexError::SendMessage)?;
+                        }
+                        Ok(())
+                    }
+                    Err(err) => Err(IndexError::BlockProcessing(err)),
+                }
+            })?;
+
+            block
+                .message()
+                .body()
+                .attestations()
+                .iter()
+                .map(|a| (a.data.slot, a.data.beacon_block_root))
+                .dedup()
+                .filter(|(slot, _)| !self.stores.indexing_state().is_before_anchor(*slot))
+                .filter(|(_, r)| {
+                    !self
+                        .stores
+                        .block_roots_cache()
+                        .write()
+                        .contains(&format!("{r:?}"))
+                })
+                .try_for_each(|(slot, root)| {
+                    info!(%slot, %root, "Unknown root while processing block {}", block.slot());
+                    self.stores.block_by_root_requests_mut().add(slot, root);
+
+                    self.consensus_command_send
+                        .send(ConsensusNetworkCommand::SendBlockByRootRequest {
+                            peer_id: None,
+                            root,
+                        })
+                        .map_err(|_| IndexError::SendMessage)
+                })?;
+
+            // Displaced blocks are kept as orphaned when the new chain missed their slot
+            displaced_blocks
+                .into_iter()
+                .flatten()
+                .try_for_each(|block| self.persist_orphaned_block(block))?;
+        }
+
+        Ok(())
+    }
+
+    /// Rolls the indexing state back when the block doesn't extend the latest processed one.
+    /// The displaced blocks are persisted as orphaned to clear their votes before the new chain
+    /// overwrites their slots.
+    /// Only a later block switches the head, unless `switch_head` is set. The blocks of already
+    /// processed slots are kept as competing until one builds on them, so that the indexed chain
+    /// doesn't flip-flop between the blocks of a slot.
+    fn handle_reorg(
+        &self,
+        block: &Arc<SignedBeaconBlock<E>>,
+        switch_head: bool,
+    ) -> Result<Option<Vec<Arc<SignedBeaconBlock<E>>>>, IndexError> {
+        let mut indexing_state = self.stores.indexing_state_mut();
+        let parent_root = block.parent_root();
+
+        if indexing_state
+            .latest_block_root()
+            .map_or(true, |root| root == parent_root)
+            || indexing_state.is_retained_block(block.canonical_root())
+        {
+            return Ok(None);
+        }
+
+        let competing_slot = indexing_state
+            .latest_slot()
+            .filter(|latest_slot| !switch_head && block.slot() <= *latest_slot);
+
+        if let Some(latest_slot) = competing_slot {
+            let reorg_depth = indexing_state.reorg_depth() as u64;
+            let mut competing_blocks = self.competing_blocks.write();
+
+            // The competing blocks older than the reorg depth can't be switched to anymore
+            competing_blocks
+                .retain(|_, competing_block| competing_block.slot() + reorg_depth > latest_slot);
+            competing_blocks.insert(block.canonical_root(), block.clone());
+
+            debug!(slot = %block.slot(), "Keeping a competing block until a later block builds on it");
+
+            return Ok(None);
+        }
+
+        match indexing_state.rollback_to(parent_root, block.slot()) {
+            Some(displaced_blocks) => {
+                warn!(
+                    slot = %block.slot(),
+                    %parent_root,
+                    displaced = displaced_blocks.len(),
+                    "Chain reorg, rolling back"
+                );
+
+                // The displaced blocks numbered their operations from the restored ids
+                let mut operation_ids = indexing_state.operation_ids();
+
+                displaced_blocks.iter().try_for_each(|displaced_block| {
+                    let displaced_block = ConsolidatedBlock::displaced(
+                        displaced_block.clone(),
+                        self.stores
+                            .clock()
+                            .timestamp(displaced_block.slot())
+                            .unwrap_or(0),
+                    )
+                    .with_first_operation_ids(operation_ids);
+
+                    operation_ids = displaced_block.next_operation_ids();
+
+                    self.work_send
+                        .send(Work::PersistBlock(displaced_block))
+                        .map_err(|_| IndexError::SendMessage)
+                })?;
+
+                Ok(Some(displaced_blocks))
+            }
+            None if Some(block.slot()) > indexing_state.latest_slot() => {
+                Err(IndexError::BlockProcessing(format!(
+                    "The block at {} doesn't extend the indexed chain and its parent {parent_root:?} can't be rolled back to",
+                    block.slot()
+                )))
+            }
+            // A competing block forking off before the retained states
+            None => {
+                warn!(
+                    slot = %block.slot(),
+                    %parent_root,
+                    reorg_depth = indexing_state.reorg_depth(),
+                    "Ignoring a block whose parent is deeper than the reorg depth"
+                );
+
+                Ok(None)
+            }
+        }
+    }
+
+    fn persist_orphaned_block(&self, block: Arc<SignedBeaconBlock<E>>) -> Result<(), IndexError> {
+        let orphaned_block = self
+            .stores
+            .indexing_state_mut()
+            .process_orphaned_block(block, self.stores.clock());
+
+        if let Some((block, aggregated_epoch)) = orphaned_block {
+            self.work_send
+                .send(Work::PersistBlock(block))
+                .map_err(|_| IndexError::SendMessage)?;
+
+            if let Some(epoch) = aggregated_epoch {
+                self.work_send
+                    .send(Work::PersistOrphanInEpoch(epoch))
+                    .map_err(|_| IndexError::SendMessage)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The latest processed slot is within an epoch of the current slot
+    fn is_caught_up(&self) -> bool {
+        match (
+            self.stores.indexing_state().latest_slot(),
+            self.stores.clock().now(),
+        ) {
+            (Some(latest_slot), Some(current_slot)) => {
+                latest_slot + E::slots_per_epoch() >= current_slot
+            }
+            _ => false,
+        }
+    }
+
     fn send_range_request(&self, to: Option<PeerId>) -> Result<(), IndexError> {
         let start_slot = self
             .stores
@@ -396,6 +602,7 @@ enum IndexError {
 pub struct BlockRangeRequests<E: EthSpec> {
     active_requests: HashSet<PeerId>,
     blocks_queue: BTreeMap<Slot, Arc<SignedBeaconBlock<E>>>,
+    following_head: bool,
 }
 
 impl<E: EthSpec> BlockRangeRequests<E> {
@@ -415,4 +622,119 @@ impl<E: EthSpec> BlockRangeRequests<E> {
     pub fn is_requesting(&self) -> bool {
         !self.active_requests.is_empty()
     }
+
+    /// Returns `false` if the head was already followed
+    pub fn follow_head(&mut self) -> bool {
+        !std::mem::replace(&mut self.following_head, true)
+    }
+
+    pub fn is_following_head(&self) -> bool {
+        self.following_head
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use lighthouse_types::MainnetEthSpec;
+    use tokio::sync::mpsc;
+
+    use crate::{
+        db::{Stores, DEFAULT_REORG_DEPTH},
+        test_utils::{temp_base_dir, BeaconChainHarness},
+        types::block_state::BlockState,
+        work::Work,
+    };
+
+    use super::{IndexError, IndexWorker};
+
+    #[tokio::test]
+    async fn test_handle_reorg() {
+        let mut harness = BeaconChainHarness::new();
+        let stores = Arc::new(Stores::<MainnetEthSpec>::new(
+            temp_base_dir("handle_reorg"),
+            harness.state(),
+            0,
+            vec![],
+            None,
+            DEFAULT_REORG_DEPTH,
+            harness.spec(),
+        ));
+
+        let (_, execution_event_recv) = mpsc::unbounded_channel();
+        let (execution_command_send, _execution_command_recv) = mpsc::unbounded_channel();
+        let (_, consensus_event_recv) = mpsc::unbounded_channel();
+        let (consensus_command_send, _consensus_command_recv) = mpsc::unbounded_channel();
+        let (work_send, mut work_recv) = mpsc::unbounded_channel();
+
+        let index_worker = IndexWorker::new(
+            execution_event_recv,
+            execution_command_send,
+            consensus_event_recv,
+            consensus_command_send,
+            work_send,
+            stores.clone(),
+        );
+
+        let at_0 = Arc::new(harness.make_block(0).await);
+        let at_1 = Arc::new(harness.make_block(1).await);
+        let state_at_1 = harness.state();
+        let at_2 = Arc::new(harness.make_block(2).await);
+        let at_3 = Arc::new(harness.make_block(3).await);
+        let at_4 = Arc::new(harness.make_block(4).await);
+        let fork_at_3 = Arc::new(harness.make_fork_block(state_at_1, 3).await);
+
+        for block in vec![&at_0, &at_1, &at_2, &at_3] {
+            stores
+                .indexing_state_mut()
+                .process_block(BlockState::Proposed(block.clone()), stores.clock())
+                .unwrap();
+        }
+
+        // Extending the latest block or an already processed one doesn't roll back
+        assert!(matches!(index_worker.handle_reorg(&at_4, false), Ok(None)));
+        assert!(matches!(index_worker.handle_reorg(&at_2, false), Ok(None)));
+
+        // A competing block of a processed slot is kept until a later block builds on it
+        assert!(matches!(
+            index_worker.handle_reorg(&fork_at_3, false),
+            Ok(None)
+        ));
+        assert_eq!(
+            stores.indexing_state().latest_block_root(),
+            Some(at_3.canonical_root())
+        );
+        assert!(index_worker
+            .competing_blocks
+            .read()
+            .contains_key(&fork_at_3.canonical_root()));
+
+        let displaced_blocks = match index_worker.handle_reorg(&fork_at_3, true) {
+            Ok(Some(displaced_blocks)) => displaced_blocks,
+            _ => panic!("The fork should roll the indexing state back"),
+        };
+
+        assert_eq!(displaced_blocks.len(), 2);
+        assert_eq!(
+            stores.indexing_state().latest_block_root(),
+            Some(at_1.canonical_root())
+        );
+
+        for displaced_block in vec![&at_2, &at_3] {
+            match work_recv.try_recv() {
+                Ok(Work::PersistBlock(block)) => {
+                    assert!(block.is_orphaned() && block.is_displaced());
+                    assert_eq!(block.root(), Some(displaced_block.canonical_root()));
+                }
+                _ => panic!("The displaced blocks should be persisted as orphaned"),
+            }
+        }
+
+        // The rolled back chain can't be extended anymore
+        assert!(matches!(
+            index_worker.handle_reorg(&at_4, false),
+            Err(IndexError::BlockProcessing(_))
+        ));
+    }
 }
@@ -1,9 +1,11 @@
+mod backfill_worker;
 mod index_worker;
 mod persist_block_worker;
 mod persist_epoch_worker;
 mod persist_validator_worker;
 
+pub use backfill_worker::spawn_backfill_worker;
 pub use index_worker::spawn_index_worker;
 pub use persist_block_worker::spawn_persist_block_worker;
-pub use persist_epoch_worker::spawn_persist_epoch_worker;
+pub use persist_epoch_worker::{spawn_persist_epoch_worker, spawn_persist_replayed_epoch_worker};
 pub use persist_validator_worker::{spawn_persist_validator_worker, ValidatorEvent};
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{fs, path::Path, sync::Arc};
 
 use lighthouse_types::EthSpec;
 use state_processing::common::get_attesting_indices;
@@ -14,14 +14,28 @@ use types::{
     block::{BlockExtendedModel, BlockExtendedModelWithId, BlockModel, BlockModelWithId},
     block_root::BlockRootModelWithId,
     committee::CommitteeModelsWithId,
+    execution_payload::{
+        ExecutionBlockNumberModel, ExecutionBlockNumberModelWithId, ExecutionPayloadModel,
+        ExecutionPayloadModelWithId,
+    },
+    path::{FromPath, ToPath},
     persistable::ResolvablePersistable,
+    slashing::{AttesterSlashingModelsWithId, ProposerSlashingModelsWithId},
+    sync_committee::{SyncAggregateModelWithId, SyncCommitteeModel, SyncCommitteeModelWithId},
     utils::{ModelCache, PersistableCache},
+    voluntary_exit::VoluntaryExitModelsWithId,
     vote::VoteModel,
+    withdrawal::{BlsToExecutionChangeModelsWithId, ValidatorWithdrawal, WithdrawalModelsWithId},
 };
 
 use crate::{
-    db::Stores,
-    types::{consolidated_block::ConsolidatedBlock, consolidated_deposits::ConsolidatedDeposits},
+    db::{graffiti_index, search_index, Stores},
+    types::{
+        attestation_performances::AttestationPerformances, block_state::BlockState,
+        consolidated_block::ConsolidatedBlock, consolidated_deposits::ConsolidatedDeposits,
+        consolidated_slashings::ConsolidatedSlashings,
+        consolidated_voluntary_exits::ConsolidatedVoluntaryExits,
+    },
 };
 
 pub fn spawn_persist_block_worker<E: EthSpec>(
@@ -34,6 +48,7 @@ pub fn spawn_persist_block_worker<E: EthSpec>(
 
     let mut extended_blocks_cache = ModelCache::new(base_dir.clone());
     let mut votes_cache = PersistableCache::new(base_dir.clone());
+    let mut attestation_performances = AttestationPerformances::restore(&base_dir);
 
     executor.spawn(
         async move {
@@ -44,7 +59,8 @@ pub fn spawn_persist_block_worker<E: EthSpec>(
                             &base_dir, block,
                             &stores,
                             &mut extended_blocks_cache,
-                            &mut votes_cache)
+                            &mut votes_cache,
+                            &mut attestation_performances)
                         {
                             error!("{err}");
                         }
@@ -52,6 +68,11 @@ pub fn spawn_persist_block_worker<E: EthSpec>(
 
                     _ = shutdown_trigger.changed() => {
                         info!("Shutting down blocks worker...");
+
+                        if let Err(err) = attestation_performances.save(&base_dir) {
+                            error!("Unable to save the open attestation performances: {err}");
+                        }
+
                         return;
                     }
                 }
@@ -70,67 +91,445 @@ fn persist_block<E: EthSpec>(
     stores: &Arc<Stores<E>>,
     extended_blocks_cache: &mut ModelCache<Option<BlockExtendedModel>>,
     votes_cache: &mut PersistableCache<Vec<VoteModel>>,
+    attestation_performances: &mut AttestationPerformances,
 ) -> Result<(), String> {
     debug!(slot = %block.slot(), "Persisting block");
+
+    if block.is_orphaned() {
+        return persist_orphaned_block(
+            base_dir,
+            &block,
+            stores,
+            extended_blocks_cache,
+            votes_cache,
+            attestation_performances,
+        );
+    }
+
+    ConsolidatedDeposits::from((&block, stores.meta_cache_mut())).save(base_dir)?;
+    ConsolidatedVoluntaryExits::from((&block, stores.meta_cache_mut())).save(base_dir)?;
+    persist_slashings(base_dir, &block, stores)?;
+
+    persist_block_models(
+        base_dir,
+        &block,
+        stores,
+        extended_blocks_cache,
+        votes_cache,
+        attestation_performances,
+    )
+}
+
+/// Replaces the block of the slot, leaving its committees untouched. The votes it included
+/// are removed, in case it was canonical before a reorg. Only an orphan of a missed slot
+/// records a missed proposal, as the new chain may have proposed the slot of a displaced block.
+fn persist_orphaned_block<E: EthSpec>(
+    base_dir: &str,
+    block: &ConsolidatedBlock<E>,
+    stores: &Arc<Stores<E>>,
+    extended_blocks_cache: &mut ModelCache<Option<BlockExtendedModel>>,
+    votes_cache: &mut PersistableCache<Vec<VoteModel>>,
+    attestation_performances: &mut AttestationPerformances,
+) -> Result<(), String> {
+    info!(slot = %block.slot(), "Persisting orphaned block");
+
+    persist_block_model(base_dir, block, stores)?;
+    BlockExtendedModelWithId::from(block).save(base_dir)?;
+    AttestationModelsWithId::from(block).save(base_dir)?;
+    Option::<BlockRootModelWithId>::from(block).save(base_dir)?;
+    WithdrawalModelsWithId::from(block).save(base_dir)?;
+    BlsToExecutionChangeModelsWithId::from(block).save(base_dir)?;
+    VoluntaryExitModelsWithId::from(block).save(base_dir)?;
+    ProposerSlashingModelsWithId::from(block).save(base_dir)?;
+    AttesterSlashingModelsWithId::from(block).save(base_dir)?;
+
+    persist_execution_payload(base_dir, block, stores)?;
+    persist_validator_withdrawals(base_dir, block, stores)?;
+    persist_validator_voluntary_exits(base_dir, block, stores)?;
+    search_index::insert(base_dir, block.search_entries())?;
+    persist_graffiti(base_dir, block)?;
+
+    let mut block_roots_cache = stores.block_roots_cache().write();
+    let included_in = block.slot().as_u64();
+
+    block
+        .orphaned_attestations()
+        .iter()
+        .for_each(|attestation| {
+            if let Some(m) =
+                block_roots_cache.get_mut(format!("{:?}", attestation.data.beacon_block_root))
+            {
+                if let Some(votes) = votes_cache.get_mut(m.model.slot) {
+                    votes.model.retain(|vote| vote.included_in != included_in);
+                }
+            }
+        });
+
+    persist_votes(extended_blocks_cache, votes_cache);
+
+    attestation_performances.remove_inclusions(included_in);
+
+    if block.is_displaced() {
+        ConsolidatedVoluntaryExits::from((block, stores.meta_cache_mut())).rewind(base_dir)?;
+        rewind_slashings(base_dir, block, stores)?;
+
+        withdraw_proposal(base_dir, block, stores)
+    } else {
+        persist_proposal(base_dir, block, stores)
+    }
+}
+
+/// Persists everything belonging to a block but its deposits, voluntary exits and slashings,
+/// whose ids depend on the indexing order.
+pub fn persist_block_models<E: EthSpec>(
+    base_dir: &str,
+    block: &ConsolidatedBlock<E>,
+    stores: &Arc<Stores<E>>,
+    extended_blocks_cache: &mut ModelCache<Option<BlockExtendedModel>>,
+    votes_cache: &mut PersistableCache<Vec<VoteModel>>,
+    attestation_performances: &mut AttestationPerformances,
+) -> Result<(), String> {
     let mut block_roots_cache = stores.block_roots_cache().write();
     let mut committees_cache = stores.committees_cache().write();
+    let mut attestations = vec![];
 
-    BlockModelWithId::from(&block).save(base_dir).unwrap();
-    BlockExtendedModelWithId::from(&block)
-        .save(base_dir)
-        .unwrap();
-    AttestationModelsWithId::from(&block)
-        .save(base_dir)
-        .unwrap();
-    CommitteeModelsWithId::from(&block).save(base_dir).unwrap();
-    Option::<BlockRootModelWithId>::from(&block)
-        .save(base_dir)
-        .unwrap();
-
-    ConsolidatedDeposits::from((&block, stores.meta_cache_mut()))
-        .save(base_dir)
-        .unwrap();
-
-    block.attestations().iter().try_for_each(|attestation| {
-        if let Some(m) =
-            block_roots_cache.get_mut(format!("{:?}", attestation.data.beacon_block_root))
-        {
-            let slot = attestation.data.slot.as_u64();
-
-            votes_cache
-                .get_or_default_mut(m.model.slot)
-                .model
-                .push(VoteModel {
-                    slot,
-                    included_in: block.slot().as_u64(),
-                    committee_index: attestation.data.index,
-                    validators: get_attesting_indices::<E>(
-                        &committees_cache
-                            .get_mut(slot)
-                            .ok_or(format!("The committee at {} can't be found", slot))?
-                            .model
-                            .get(attestation.data.index as usize)
-                            .ok_or(format!(
-                                "The attestation '{}' is out of bound of committees at slot {slot}",
-                                attestation.data.index
-                            ))?
-                            .validators,
-                        &attestation.aggregation_bits,
-                    )
-                    .map_err(|err| {
-                        format!(
-                            "Error while getting attesting indices on slot {} for committee index{}: {err:?}",
-                            block.slot(),
+    persist_block_model(base_dir, block, stores)?;
+    BlockExtendedModelWithId::from(block).save(base_dir)?;
+    AttestationModelsWithId::from(block).save(base_dir)?;
+    CommitteeModelsWithId::from(block).save(base_dir)?;
+    Option::<BlockRootModelWithId>::from(block).save(base_dir)?;
+    SyncAggregateModelWithId::from(block).save(base_dir)?;
+    WithdrawalModelsWithId::from(block).save(base_dir)?;
+    BlsToExecutionChangeModelsWithId::from(block).save(base_dir)?;
+    VoluntaryExitModelsWithId::from(block).save(base_dir)?;
+    ProposerSlashingModelsWithId::from(block).save(base_dir)?;
+    AttesterSlashingModelsWithId::from(block).save(base_dir)?;
+
+    persist_sync_committees(base_dir, block)?;
+    persist_execution_payload(base_dir, block, stores)?;
+    persist_validator_withdrawals(base_dir, block, stores)?;
+    persist_validator_voluntary_exits(base_dir, block, stores)?;
+    search_index::insert(base_dir, block.search_entries())?;
+    persist_graffiti(base_dir, block)?;
+
+    block
+        .attestations_with_correctness()
+        .into_iter()
+        .try_for_each(|(attestation, correctness)| {
+            if let Some(m) =
+                block_roots_cache.get_mut(format!("{:?}", attestation.data.beacon_block_root))
+            {
+                let slot = attestation.data.slot.as_u64();
+                let validators = get_attesting_indices::<E>(
+                    &committees_cache
+                        .get_mut(slot)
+                        .ok_or(format!("The committee at {} can't be found", slot))?
+                        .model
+                        .get(attestation.data.index as usize)
+                        .ok_or(format!(
+                            "The attestation '{}' is out of bound of committees at slot {slot}",
                             attestation.data.index
-                        )
-                    })
-                    .unwrap_or_default(),
-                });
+                        ))?
+                        .validators,
+                    &attestation.aggregation_bits,
+                )
+                .map_err(|err| {
+                    format!(
+                        "Error while getting attesting indices on slot {} for committee index{}: {err:?}",
+                        block.slot(),
+                        attestation.data.index
+                    )
+                })
+                .unwrap_or_default();
+
+                votes_cache
+                    .get_or_default_mut(m.model.slot)
+                    .model
+                    .push(VoteModel {
+                        slot,
+                        included_in: block.slot().as_u64(),
+                        committee_index: attestation.data.index,
+                        validators: validators.clone(),
+                    });
+
+                attestations.push((attestation, correctness, validators));
+            }
+
+            Ok::<_, String>(())
+        })?;
+
+    persist_votes(extended_blocks_cache, votes_cache);
+
+    attestation_performances.consolidate(block, &attestations);
+    attestation_performances.persist_closed(base_dir, block.epoch().as_u64())?;
+
+    persist_proposal(base_dir, block, stores)?;
+
+    stores
+        .meta_cache_mut()
+        .loaded_entry::<BlockModel>()
+        .mark_indexed(block.slot().as_u64())
+        .save::<BlockModel>(base_dir)?;
+
+    Ok(())
+}
+
+/// Persists the summary of the block and registers it for the next sorted indexes merge
+fn persist_block_model<E: EthSpec>(
+    base_dir: &str,
+    block: &ConsolidatedBlock<E>,
+    stores: &Arc<Stores<E>>,
+) -> Result<(), String> {
+    let block_model = BlockModelWithId::from(block);
+
+    block_model.save(base_dir)?;
+    stores.pending_sortables_mut().blocks.push(&block_model);
+
+    Ok(())
+}
+
+/// Persists the sync committees of the block period and the next one, when not persisted yet
+fn persist_sync_committees<E: EthSpec>(
+    base_dir: &str,
+    block: &ConsolidatedBlock<E>,
+) -> Result<(), String> {
+    match block.sync_committees() {
+        Some(sync_committees) => Vec::<SyncCommitteeModelWithId>::from(sync_committees)
+            .into_iter()
+            .filter(|m| !Path::new(&SyncCommitteeModel::to_path(base_dir, &m.id)).exists())
+            .try_for_each(|m| m.save(base_dir)),
+        None => Ok(()),
+    }
+}
+
+/// Adds the slashings of a canonical block to the global list, and links the slashed validators
+/// to the first slashing which hit them
+fn persist_slashings<E: EthSpec>(
+    base_dir: &str,
+    block: &ConsolidatedBlock<E>,
+    stores: &Arc<Stores<E>>,
+) -> Result<(), String> {
+    let slashings = ConsolidatedSlashings::from((block, stores.meta_cache_mut())).save(base_dir)?;
+    let mut validators_cache = stores.validators_cache().write();
+
+    slashings.iter().try_for_each(|slashing| {
+        stores.pending_sortables_mut().slashings.push(slashing);
+
+        slashing
+            .model
+            .slashed_indices
+            .iter()
+            .try_for_each(|validator_index| {
+                validators_cache.update_and_save(*validator_index, |validator| {
+                    validator.model.slashed = true;
+                    validator.model.slashing.get_or_insert(slashing.id);
+                })
+            })
+    })
+}
+
+/// Removes the slashings of a displaced block from the global list, and unlinks the validators
+/// whose first slashing they were
+fn rewind_slashings<E: EthSpec>(
+    base_dir: &str,
+    block: &ConsolidatedBlock<E>,
+    stores: &Arc<Stores<E>>,
+) -> Result<(), String> {
+    let slashings =
+        ConsolidatedSlashings::from((block, stores.meta_cache_mut())).rewind(base_dir)?;
+    let mut validators_cache = stores.validators_cache().write();
+
+    slashings.iter().try_for_each(|slashing| {
+        stores.pending_sortables_mut().slashings.remove(slashing.id);
+
+        slashing
+            .model
+            .slashed_indices
+            .iter()
+            .try_for_each(|validator_index| {
+                validators_cache.update_and_save(*validator_index, |validator| {
+                    if validator.model.slashing == Some(slashing.id) {
+                        validator.model.slashed = false;
+                        validator.model.slashing = None;
+                    }
+                })
+            })
+    })
+}
+
+/// Persists the execution payload of a post-Merge block, counting the slots which have one.
+/// The execution block number is mapped to the slot as long as the block is canonical, the
+/// mapping of a displaced block being removed unless the new chain already replaced it.
+fn persist_execution_payload<E: EthSpec>(
+    base_dir: &str,
+    block: &ConsolidatedBlock<E>,
+    stores: &Arc<Stores<E>>,
+) -> Result<(), String> {
+    let execution_payload = match Option::<ExecutionPayloadModelWithId>::from(block) {
+        Some(execution_payload) => execution_payload,
+        None => return Ok(()),
+    };
+
+    let is_new = !Path::new(&ExecutionPayloadModel::to_path(
+        base_dir,
+        &execution_payload.id,
+    ))
+    .exists();
+
+    if !block.is_orphaned() {
+        ExecutionBlockNumberModelWithId {
+            id: execution_payload.model.block_number,
+            model: ExecutionBlockNumberModel {
+                slot: execution_payload.id,
+            },
         }
+        .save(base_dir)?;
+    } else if block.is_displaced() {
+        let block_number = execution_payload.model.block_number;
+
+        if matches!(
+            ExecutionBlockNumberModel::from_path(base_dir, &block_number),
+            Ok(model) if model.slot == execution_payload.id
+        ) {
+            let path = ExecutionBlockNumberModel::to_path(base_dir, &block_number);
+
+            fs::remove_file(&path).map_err(|err| format!("Failed to remove '{path}': {err}"))?;
+        }
+    }
+
+    execution_payload.save(base_dir)?;
+    stores
+        .pending_sortables_mut()
+        .execution_payloads
+        .push(&execution_payload);
+
+    if is_new {
+        stores
+            .meta_cache_mut()
+            .loaded_entry::<ExecutionPayloadModel>()
+            .increment()
+            .save::<ExecutionPayloadModel>(base_dir)?;
+    }
+
+    Ok(())
+}
+
+/// Indexes the slot of the block by its graffiti, for graffiti which could be decoded
+fn persist_graffiti<E: EthSpec>(
+    base_dir: &str,
+    block: &ConsolidatedBlock<E>,
+) -> Result<(), String> {
+    match block.graffiti() {
+        Some(graffiti) => graffiti_index::update(
+            base_dir,
+            &graffiti,
+            block.slot().as_u64(),
+            !block.is_orphaned(),
+        ),
+        None => Ok(()),
+    }
+}
+
+/// Adds the withdrawals of a canonical block to the history of their validators,
+/// or removes them if the block has been orphaned
+fn persist_validator_withdrawals<E: EthSpec>(
+    base_dir: &str,
+    block: &ConsolidatedBlock<E>,
+    stores: &Arc<Stores<E>>,
+) -> Result<(), String> {
+    let slot = block.slot().as_u64();
+    let mut validators_extended_cache = stores.validators_extended_cache().write();
+
+    WithdrawalModelsWithId::from(block)
+        .model
+        .iter()
+        .try_for_each(|withdrawal| {
+            let validator_extended =
+                validators_extended_cache.get_or_default_mut(withdrawal.validator_index);
+            let withdrawals = &mut validator_extended.model.withdrawals;
 
-        Ok::<_, String>(())
-    })?;
+            withdrawals.retain(|w| w.slot != slot);
 
+            if !block.is_orphaned() {
+                withdrawals.push(ValidatorWithdrawal::from((slot, withdrawal)));
+                withdrawals.sort_by_key(|w| w.index);
+            }
+
+            validator_extended.save(base_dir)
+        })
+}
+
+/// Links the validators to the canonical block which included their voluntary exit,
+/// or unlinks them if the block has been orphaned
+fn persist_validator_voluntary_exits<E: EthSpec>(
+    base_dir: &str,
+    block: &ConsolidatedBlock<E>,
+    stores: &Arc<Stores<E>>,
+) -> Result<(), String> {
+    let slot = block.slot().as_u64();
+    let mut validators_extended_cache = stores.validators_extended_cache().write();
+
+    block
+        .voluntary_exits()
+        .iter()
+        .try_for_each(|voluntary_exit| {
+            let validator_extended = validators_extended_cache
+                .get_or_default_mut(voluntary_exit.message.validator_index);
+            let voluntary_exit_slot = &mut validator_extended.model.voluntary_exit_slot;
+
+            if !block.is_orphaned() {
+                *voluntary_exit_slot = Some(slot);
+            } else if *voluntary_exit_slot == Some(slot) {
+                *voluntary_exit_slot = None;
+            }
+
+            validator_extended.save(base_dir)
+        })
+}
+
+/// Records the slot as proposed or missed by its proposer
+fn persist_proposal<E: EthSpec>(
+    base_dir: &str,
+    block: &ConsolidatedBlock<E>,
+    stores: &Arc<Stores<E>>,
+) -> Result<(), String> {
+    let slot = block.slot().as_u64();
+    let mut validators_extended_cache = stores.validators_extended_cache().write();
+    let validator_extended = validators_extended_cache.get_or_default_mut(block.proposer_index());
+    let model = &mut validator_extended.model;
+
+    if let BlockState::Proposed(_) = block.block() {
+        model.missed_slots.remove(&slot);
+        model.proposed_slots.insert(slot);
+    } else {
+        model.proposed_slots.remove(&slot);
+        model.missed_slots.insert(slot);
+    }
+
+    validator_extended.save(base_dir)
+}
+
+/// Forgets the proposal of a displaced block, the new chain recording the slot once processed
+fn withdraw_proposal<E: EthSpec>(
+    base_dir: &str,
+    block: &ConsolidatedBlock<E>,
+    stores: &Arc<Stores<E>>,
+) -> Result<(), String> {
+    let slot = block.slot().as_u64();
+    let mut validators_extended_cache = stores.validators_extended_cache().write();
+    let validator_extended = validators_extended_cache.get_or_default_mut(block.proposer_index());
+
+    if validator_extended.model.proposed_slots.remove(&slot) {
+        validator_extended.save(base_dir)
+    } else {
+        Ok(())
+    }
+}
+
+fn persist_votes(
+    extended_blocks_cache: &mut ModelCache<Option<BlockExtendedModel>>,
+    votes_cache: &mut PersistableCache<Vec<VoteModel>>,
+) {
     votes_cache.dirty_iter().for_each(|votes| {
         if let Err(err) = extended_blocks_cache.update_and_save(votes.id, |block_extended| {
             if let Some(model) = &mut block_extended.model {
@@ -145,13 +544,4 @@ fn persist_block<E: EthSpec>(
     });
 
     votes_cache.persist_dirty();
-
-    stores
-        .meta_cache_mut()
-        .entry::<BlockModel>()
-        .update_count(block.slot().as_usize() + 1)
-        .save::<BlockModel>(base_dir)
-        .unwrap();
-
-    Ok(())
 }
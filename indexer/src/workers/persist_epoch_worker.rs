@@ -2,14 +2,19 @@ use std::sync::Arc;
 
 use lighthouse_types::EthSpec;
 use task_executor::TaskExecutor;
-use tracing::{info, instrument};
+use tracing::{debug, error, info, instrument};
 use types::{
     epoch::{EpochExtendedModelWithId, EpochModel, EpochModelWithId},
+    graffiti::GraffitiTallyModelWithId,
+    path::FromPath,
     persistable::ResolvablePersistable,
+    validator_balances::{ValidatorBalancesModel, ValidatorBalancesModelWithId},
 };
 
 use crate::{db::Stores, types::consolidated_epoch::ConsolidatedEpoch};
 
+use super::persist_validator_worker::save_validator;
+
 pub fn spawn_persist_epoch_worker<E: EthSpec>(
     base_dir: String,
     epoch: ConsolidatedEpoch<E>,
@@ -19,24 +24,106 @@ pub fn spawn_persist_epoch_worker<E: EthSpec>(
     let stores = stores.clone();
 
     executor.spawn(
-        async move { persist_epoch(&base_dir, epoch, &stores) },
+        async move { persist_epoch(&base_dir, epoch, &stores, true) },
         "persist epoch worker",
     );
 }
 
+/// Persists an epoch replayed by the backfill, but its changed validators, whose state is older
+/// than the one already persisted from the anchor on
+pub fn spawn_persist_replayed_epoch_worker<E: EthSpec>(
+    base_dir: String,
+    epoch: ConsolidatedEpoch<E>,
+    stores: &Arc<Stores<E>>,
+    executor: &TaskExecutor,
+) {
+    let stores = stores.clone();
+
+    executor.spawn(
+        async move { persist_epoch(&base_dir, epoch, &stores, false) },
+        "persist replayed epoch worker",
+    );
+}
+
 #[instrument(name = "EpochPersist", fields(duration), skip_all)]
-fn persist_epoch<E: EthSpec>(base_dir: &str, epoch: ConsolidatedEpoch<E>, stores: &Arc<Stores<E>>) {
+fn persist_epoch<E: EthSpec>(
+    base_dir: &str,
+    epoch: ConsolidatedEpoch<E>,
+    stores: &Arc<Stores<E>>,
+    with_validators: bool,
+) {
     info!(%epoch, "Persisting epoch");
 
-    EpochModelWithId::from(&epoch).save(base_dir).unwrap();
+    let mut epoch_model = EpochModelWithId::from(&epoch);
+    let mut orphaned_blocks = stores.orphaned_blocks_mut();
+
+    orphaned_blocks.apply(&mut epoch_model);
+    epoch_model.save(base_dir).unwrap();
+
+    drop(orphaned_blocks);
+
+    stores.pending_sortables_mut().epochs.push(&epoch_model);
+
     EpochExtendedModelWithId::from(&epoch)
         .save(base_dir)
         .unwrap();
+    GraffitiTallyModelWithId::from(&epoch)
+        .save(base_dir)
+        .unwrap();
+
+    if with_validators {
+        if let Err(err) = persist_changed_validators(base_dir, &epoch, stores) {
+            error!(%epoch, "Unable to persist the changed validators: {err}");
+        }
+    }
+
+    if let Err(err) = persist_validator_balances(base_dir, &epoch) {
+        error!(%epoch, "Unable to persist the validator balances: {err}");
+    }
 
     stores
         .meta_cache_mut()
-        .entry::<EpochModel>()
-        .update_count(epoch.number() + 1)
+        .loaded_entry::<EpochModel>()
+        .mark_indexed(epoch.number() as u64)
         .save::<EpochModel>(base_dir)
         .unwrap();
+
+    if let Err(err) = stores.merge_sortable_indexes(base_dir) {
+        error!(%epoch, "Unable to merge the sorted indexes: {err}");
+    }
+}
+
+/// Rewrites the validators whose state changed during the epoch
+fn persist_changed_validators<E: EthSpec>(
+    base_dir: &str,
+    epoch: &ConsolidatedEpoch<E>,
+    stores: &Arc<Stores<E>>,
+) -> Result<(), String> {
+    debug!(%epoch, count = epoch.changed_validators().len(), "Persisting changed validators");
+
+    epoch
+        .changed_validators()
+        .iter()
+        .try_for_each(|(validator_index, model)| {
+            save_validator(base_dir, stores, *validator_index, model.clone())
+        })
+}
+
+/// Appends the balances at the end of the epoch to the chunk of every validator
+fn persist_validator_balances<E: EthSpec>(
+    base_dir: &str,
+    epoch: &ConsolidatedEpoch<E>,
+) -> Result<(), String> {
+    let epoch_number = epoch.number() as u64;
+
+    epoch
+        .validator_balances()
+        .try_for_each(|(validator_index, balance, effective_balance)| {
+            let id = ValidatorBalancesModel::chunk_id(validator_index, epoch_number);
+            let mut model = ValidatorBalancesModel::from_path(base_dir, &id).unwrap_or_default();
+
+            model.record(epoch_number, balance, effective_balance);
+
+            ValidatorBalancesModelWithId { id, model }.save(base_dir)
+        })
 }
@@ -1,4 +1,4 @@
-use std::{collections::HashSet, sync::Arc};
+use std::sync::Arc;
 
 use lighthouse_types::EthSpec;
 use task_executor::TaskExecutor;
@@ -6,11 +6,11 @@ use tokio::sync::mpsc::{unbounded_channel, Sender, UnboundedSender};
 use tracing::error;
 use types::{
     deposit::ExecutionLayerDepositModelWithId, persistable::ResolvablePersistable,
-    validator::ValidatorExtendedModel,
+    validator::ValidatorModel,
 };
 
 use crate::{
-    db::Stores,
+    db::{search_index, validator_lookups, Stores},
     types::{
         consolidated_execution_layer_deposit::ConsolidatedExecutionLayerDeposit,
         consolidated_validator::ConsolidatedValidator,
@@ -44,6 +44,9 @@ pub fn spawn_persist_validator_worker<E: EthSpec>(
                             .save(&base_dir)
                             .unwrap();
 
+                        search_index::insert(&base_dir, consolidated_deposit.search_entries())
+                            .unwrap();
+
                         if let Some((validator, balance)) = validators
                             .get(validator_index)
                             .and_then(|v| balances.get(validator_index).map(|b| (v, b)))
@@ -51,30 +54,25 @@ pub fn spawn_persist_validator_worker<E: EthSpec>(
                             let consolidated_validator =
                                 ConsolidatedValidator::<E>::new(validator, current_epoch, balance);
 
-                            stores
-                                .validators_cache()
-                                .write()
-                                .entry(consolidated_deposit.validator_index)
-                                .update_or_insert(consolidated_validator.into())
-                                .save(&base_dir)
-                                .unwrap();
-
-                            stores
-                                .validators_extended_cache()
-                                .write()
-                                .entry(consolidated_deposit.validator_index)
-                                .and_modify(|v| {
-                                    v.model
-                                        .execution_layer_deposits
-                                        .insert(consolidated_deposit.index);
-                                })
-                                .or_insert_with(|| ValidatorExtendedModel {
-                                    execution_layer_deposits: HashSet::from([
-                                        consolidated_deposit.index
-                                    ]),
-                                })
-                                .save(&base_dir)
-                                .unwrap();
+                            save_validator(
+                                &base_dir,
+                                &stores,
+                                consolidated_deposit.validator_index,
+                                consolidated_validator.into(),
+                            )
+                            .unwrap();
+
+                            let mut validators_extended_cache =
+                                stores.validators_extended_cache().write();
+                            let validator_extended = validators_extended_cache
+                                .get_or_default_mut(consolidated_deposit.validator_index);
+
+                            validator_extended
+                                .model
+                                .execution_layer_deposits
+                                .insert(consolidated_deposit.index);
+
+                            validator_extended.save(&base_dir).unwrap();
                         } else {
                             error!(
                                 "The validator '{}' can't be found in the beacon state",
@@ -90,3 +88,30 @@ pub fn spawn_persist_validator_worker<E: EthSpec>(
 
     validator_event_send
 }
+
+/// Replaces the validator with its latest state, keeping what can't be derived from it
+pub fn save_validator<E: EthSpec>(
+    base_dir: &str,
+    stores: &Arc<Stores<E>>,
+    validator_index: u64,
+    mut model: ValidatorModel,
+) -> Result<(), String> {
+    let mut validators_cache = stores.validators_cache().write();
+    let previous = validators_cache.get_mut(validator_index);
+
+    if let Some(previous) = &previous {
+        model.slashing = previous.model.slashing;
+    }
+
+    validator_lookups::update(
+        base_dir,
+        validator_index,
+        previous.map(|previous| &previous.model),
+        &model,
+    )?;
+
+    validators_cache
+        .entry(validator_index)
+        .update_or_insert(model)
+        .save(base_dir)
+}
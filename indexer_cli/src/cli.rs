@@ -1,4 +1,7 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use indexer::launcher::{
+    DEFAULT_KEPT_SNAPSHOTS_COUNT, DEFAULT_REORG_DEPTH, DEFAULT_SORTABLE_CHUNK_SIZE,
+};
 
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
@@ -11,6 +14,10 @@ pub struct Cli {
 
     #[clap(long, env)]
     pub execution_node_url: String,
+
+    /// Name of a known network (mainnet, goerli, sepolia, holesky) or path to a testnet directory
+    #[clap(long, default_value = "mainnet")]
+    pub network: String,
 }
 
 #[derive(Debug, Subcommand)]
@@ -21,7 +28,78 @@ pub enum Commands {
 
         #[clap(long, conflicts_with("reset"))]
         dry: bool,
+
+        /// SSZ encoded finalized state to start indexing from instead of genesis
+        #[clap(long, requires("checkpoint-block"))]
+        checkpoint_state: Option<String>,
+
+        /// SSZ encoded block matching the checkpoint state
+        #[clap(long, requires("checkpoint-state"))]
+        checkpoint_block: Option<String>,
+
+        /// Start indexing from the finalized checkpoint of the beacon node instead of genesis
+        #[clap(long, requires("beacon-node-url"), conflicts_with("checkpoint-state"))]
+        checkpoint_sync: bool,
+
+        /// Index the history before the checkpoint in the background
+        #[clap(long, conflicts_with("dry"))]
+        backfill: bool,
+
+        /// Where the consensus blocks are fetched from
+        #[clap(long, value_enum, default_value = "p2p")]
+        source: Source,
+
+        /// Beacon node REST API used by the beacon-api source
+        #[clap(long, env, required_if_eq("source", "beacon-api"))]
+        beacon_node_url: Option<String>,
+
+        /// How many snapshots of the indexing state are kept to recover from
+        #[clap(long, default_value_t = DEFAULT_KEPT_SNAPSHOTS_COUNT)]
+        kept_snapshots: usize,
+
+        /// How many slots can be rolled back when the head is reorged, holding a state each
+        #[clap(long, default_value_t = DEFAULT_REORG_DEPTH)]
+        reorg_depth: usize,
+    },
+
+    /// Rebuild the sorted indexes from every model, while the indexer isn't running.
+    /// The indexer keeps them up to date, so this is only needed to change their chunk size or
+    /// for databases indexed before they were maintained incrementally.
+    UpdateIndexes {
+        /// How many ids each file of a sorted index holds
+        #[clap(long, default_value_t = DEFAULT_SORTABLE_CHUNK_SIZE, value_parser = parse_chunk_size)]
+        chunk_size: usize,
     },
 
-    UpdateIndexes,
+    /// Manage the snapshots of the indexing state
+    Snapshots {
+        #[clap(subcommand)]
+        command: SnapshotsCommand,
+    },
+}
+
+fn parse_chunk_size(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(0) => Err("The chunk size must be at least 1".to_string()),
+        Ok(chunk_size) => Ok(chunk_size),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum Source {
+    P2p,
+    BeaconApi,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SnapshotsCommand {
+    /// List the slots of the available snapshots
+    List,
+
+    /// Remove all but the most recent snapshots
+    Prune {
+        #[clap(long, default_value = "1")]
+        keep: usize,
+    },
 }
@@ -1,9 +1,9 @@
 #![recursion_limit = "256"]
 
 use clap::StructOpt;
-use cli::Commands;
+use cli::{Commands, SnapshotsCommand, Source};
 use dotenv::dotenv;
-use indexer::launcher;
+use indexer::launcher::{self, CheckpointSource, Source as LauncherSource};
 use span_duration::SpanDurationLayer;
 use tracing_subscriber::{
     filter::LevelFilter, prelude::__tracing_subscriber_SubscriberExt, util::SubscriberInitExt,
@@ -35,9 +35,55 @@ fn main() {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::BuildDatabase { reset, dry } => {
-            launcher::start_indexer(reset, dry, cli.base_dir, cli.execution_node_url).unwrap()
+        Commands::BuildDatabase {
+            reset,
+            dry,
+            checkpoint_state,
+            checkpoint_block,
+            checkpoint_sync,
+            backfill,
+            source,
+            beacon_node_url,
+            kept_snapshots,
+            reorg_depth,
+        } => {
+            let checkpoint = match (checkpoint_state.zip(checkpoint_block), &beacon_node_url) {
+                (Some((state_path, block_path)), _) => {
+                    Some(CheckpointSource::Files(state_path, block_path))
+                }
+                (None, Some(beacon_node_url)) if checkpoint_sync => {
+                    Some(CheckpointSource::BeaconNode(beacon_node_url.clone()))
+                }
+                _ => None,
+            };
+
+            launcher::start_indexer(
+                reset,
+                dry,
+                backfill,
+                match (source, beacon_node_url) {
+                    (Source::BeaconApi, Some(beacon_node_url)) => {
+                        LauncherSource::BeaconApi { beacon_node_url }
+                    }
+                    _ => LauncherSource::P2p,
+                },
+                kept_snapshots,
+                reorg_depth,
+                cli.base_dir,
+                cli.execution_node_url,
+                cli.network,
+                checkpoint,
+            )
+            .unwrap()
+        }
+        Commands::UpdateIndexes { chunk_size } => {
+            launcher::update_indexes(cli.base_dir, chunk_size).unwrap()
         }
-        Commands::UpdateIndexes => launcher::update_indexes(cli.base_dir).unwrap(),
+        Commands::Snapshots { command } => match command {
+            SnapshotsCommand::List => launcher::list_snapshots(cli.base_dir).unwrap(),
+            SnapshotsCommand::Prune { keep } => {
+                launcher::prune_snapshots(cli.base_dir, keep).unwrap()
+            }
+        },
     }
 }
@@ -82,7 +82,8 @@ pub fn persistable(input: TokenStream) -> TokenStream {
                         })
                     }
 
-                    fn persist_sortables(self, base_dir: &str) -> Result<(), String>
+                    /// Rebuilds the sorted indexes in chunks of `chunk_size` ids, recording it in the meta
+                    fn persist_sortables(self, base_dir: &str, chunk_size: usize) -> Result<(), String>
                     where
                         Self: Sized,
                     {
@@ -90,13 +91,23 @@ pub fn persistable(input: TokenStream) -> TokenStream {
                         let prefixed_dir = format!("{}/{}", base_dir, prefix);
                         #( let mut #heap_fields = crate::utils::FieldBinaryHeap::<#model_id, #heap_types>::new(); )*
 
+                        let mut length = 0;
+
                         for m in self {
+                            length += 1;
                             #( #heap_fields.push(#orderables); )*
                         }
 
-                        #( #heap_fields.persist(&prefixed_dir, #field_names)?; )*
+                        #( #heap_fields.persist(&prefixed_dir, #field_names, chunk_size)?; )*
+
+                        let meta_path = crate::meta::Meta::to_path::<Self::Item>(base_dir);
+                        let mut meta = <crate::meta::Meta as crate::persistable::MsgPackDeserializable>::deserialize_from_file(&meta_path)
+                            .unwrap_or_default();
 
-                        Ok(())
+                        #( meta.sortable_chunk_sizes.insert(String::from(#field_names), chunk_size); )*
+                        #( meta.sortable_lengths.insert(String::from(#field_names), length); )*
+
+                        meta.save::<Self::Item>(base_dir)
                     }
                 }
 
@@ -106,10 +117,104 @@ pub fn persistable(input: TokenStream) -> TokenStream {
         _ => None,
     };
 
+    let pending_sortables = match opts.model {
+        Model::Default if !field_names.is_empty() => {
+            let pending_sortables = format_ident!("PendingSortables{}", model_ident);
+            Some(quote! {
+                /// The orderables of the models persisted or removed since the sorted indexes were last merged
+                #[derive(Default)]
+                pub struct #pending_sortables {
+                    #( #heap_fields: std::collections::BTreeMap<#model_id, #heap_types>, )*
+                    removed: std::collections::BTreeSet<#model_id>,
+                }
+
+                impl #pending_sortables {
+                    /// Registers a persisted model, replacing its previous orderables
+                    pub fn push(&mut self, m: &#model_with_id) {
+                        self.removed.remove(&m.id);
+
+                        #(
+                            let orderable: crate::utils::Orderable<#model_id, #heap_types> = #orderables;
+                            self.#heap_fields.insert(orderable.id, orderable.ordering);
+                        )*
+                    }
+
+                    /// Registers a removed model, whose entries are dropped from the sorted indexes
+                    pub fn remove(&mut self, id: #model_id) {
+                        #( self.#heap_fields.remove(&id); )*
+
+                        self.removed.insert(id);
+                    }
+
+                    pub fn is_empty(&self) -> bool {
+                        self.removed.is_empty() #( && self.#heap_fields.is_empty() )*
+                    }
+                }
+
+                impl crate::utils::PendingSortable for #pending_sortables {
+                    fn merge(
+                        &self,
+                        base_dir: &str,
+                        meta: &crate::meta::Meta,
+                    ) -> Result<std::collections::BTreeMap<String, usize>, String> {
+                        let prefix = <#model_with_id as crate::path::Prefix>::prefix();
+                        let prefixed_dir = format!("{}/{}", base_dir, prefix);
+                        let mut lengths = std::collections::BTreeMap::new();
+
+                        if self.is_empty() {
+                            return Ok(lengths);
+                        }
+
+                        #(
+                            let length = crate::utils::SortableRun::<#model_id, #heap_types>::merge(
+                                &prefixed_dir,
+                                #field_names,
+                                &self.#heap_fields,
+                                &self.removed,
+                                meta.sortable_chunk_size(#field_names),
+                                |id| {
+                                    let m = #model_with_id {
+                                        id: id.clone(),
+                                        model: <#model_ident as crate::path::FromPath>::from_path(base_dir, id)?,
+                                    };
+                                    let orderable: crate::utils::Orderable<#model_id, #heap_types> = #orderables;
+
+                                    Ok(orderable.ordering)
+                                },
+                            )?;
+                            lengths.insert(String::from(#field_names), length);
+                        )*
+
+                        Ok(lengths)
+                    }
+
+                    fn requeue(self, pending: &mut Self) {
+                        #(
+                            for (id, ordering) in self.#heap_fields {
+                                if !pending.removed.contains(&id) {
+                                    pending.#heap_fields.entry(id).or_insert(ordering);
+                                }
+                            }
+                        )*
+
+                        for id in self.removed {
+                            if true #( && !pending.#heap_fields.contains_key(&id) )* {
+                                pending.removed.insert(id);
+                            }
+                        }
+                    }
+                }
+            })
+        }
+        _ => None,
+    };
+
     let persistable_related = quote! {
         pub type #model_with_id = crate::model::ModelWithId<#model_ty>;
 
         #persist_iterator
+
+        #pending_sortables
     };
 
     let expanded = quote! {
@@ -4,21 +4,26 @@ use chrono::{TimeZone, Utc};
 use lighthouse_types::{ChainSpec, Slot};
 use slot_clock::{SlotClock, SystemTimeSlotClock};
 
+#[derive(Clone)]
 pub struct Clock {
     clock: SystemTimeSlotClock,
 }
 
 impl Clock {
-    pub fn new(spec: ChainSpec) -> Self {
+    pub fn new(genesis_time: u64, spec: &ChainSpec) -> Self {
         Clock {
             clock: SystemTimeSlotClock::new(
                 spec.genesis_slot,
-                Duration::from_secs(1606824023),
+                Duration::from_secs(genesis_time),
                 Duration::from_secs(spec.seconds_per_slot),
             ),
         }
     }
 
+    pub fn now(&self) -> Option<Slot> {
+        self.clock.now()
+    }
+
     pub fn start_of(&self, slot: Slot) -> Option<Duration> {
         self.clock.start_of(slot)
     }
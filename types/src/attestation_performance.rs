@@ -0,0 +1,66 @@
+use indexer_macro::Persistable;
+use serde::Deserialize;
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+
+/// How many epochs a chunk of attestation performances covers
+pub const ATTESTATION_PERFORMANCES_CHUNK_EPOCHS: u64 = 256;
+
+/// The attestation duty of a validator in an epoch and how it has been fulfilled. The duties of a
+/// validator are persisted in chunks of `ATTESTATION_PERFORMANCES_CHUNK_EPOCHS` epochs.
+#[derive(Persistable, Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[persistable(id = "String")]
+#[persistable(model = "collection")]
+#[persistable(prefix = "/validators/a")]
+#[serde(rename_all = "camelCase")]
+pub struct AttestationPerformanceModel {
+    pub epoch: u64,
+    pub slot: u64,
+    pub committee_index: u64,
+    pub included_in: Option<u64>,
+    pub inclusion_distance: Option<u64>,
+    pub correct_source: bool,
+    pub correct_target: bool,
+    pub correct_head: bool,
+}
+
+impl AttestationPerformanceModel {
+    /// Id of the chunk containing `epoch` for a validator
+    pub fn chunk_id(validator_index: u64, epoch: u64) -> String {
+        format!(
+            "{validator_index}_{}",
+            epoch / ATTESTATION_PERFORMANCES_CHUNK_EPOCHS
+        )
+    }
+
+    /// Ids of the chunks covering `start_epoch..end_epoch` for a validator
+    pub fn chunk_ids(validator_index: u64, start_epoch: u64, end_epoch: u64) -> Vec<String> {
+        if start_epoch >= end_epoch {
+            return vec![];
+        }
+
+        (start_epoch / ATTESTATION_PERFORMANCES_CHUNK_EPOCHS
+            ..=end_epoch.saturating_sub(1) / ATTESTATION_PERFORMANCES_CHUNK_EPOCHS)
+            .map(|chunk| format!("{validator_index}_{chunk}"))
+            .collect()
+    }
+
+    pub fn missed(epoch: u64, slot: u64, committee_index: u64) -> Self {
+        AttestationPerformanceModel {
+            epoch,
+            slot,
+            committee_index,
+            included_in: None,
+            inclusion_distance: None,
+            correct_source: false,
+            correct_target: false,
+            correct_head: false,
+        }
+    }
+
+    pub fn is_missed(&self) -> bool {
+        self.included_in.is_none()
+    }
+}
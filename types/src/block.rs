@@ -42,4 +42,6 @@ pub struct BlockExtendedModel {
     pub eth1data_deposit_root: String,
     pub eth1data_deposit_count: u64,
     pub eth1data_block_hash: String,
+    #[serde(default)]
+    pub graffiti_text: Option<String>,
 }
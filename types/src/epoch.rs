@@ -38,6 +38,11 @@ pub struct EpochModel {
     pub eligible_ether: u64,
     #[persistable(sortable)]
     pub voted_ether: u64,
+    #[persistable(sortable)]
+    #[serde(default)]
+    pub withdrawals_count: usize,
+    #[serde(default)]
+    pub bls_to_execution_changes_count: usize,
 }
 
 fn get_global_participation_rate(value: &EpochModelWithId) -> Orderable<u64, OrderedFloat<f64>> {
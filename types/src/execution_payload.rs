@@ -0,0 +1,38 @@
+use indexer_macro::Persistable;
+use serde::Deserialize;
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+
+/// The execution block included in a post-Merge block, by slot
+#[derive(Persistable, Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[persistable(model = "default")]
+#[persistable(prefix = "/execution_payloads")]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi))]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionPayloadModel {
+    #[persistable(sortable)]
+    pub block_number: u64,
+    pub block_hash: String,
+    pub parent_hash: String,
+    pub fee_recipient: String,
+    #[persistable(sortable)]
+    pub gas_used: u64,
+    pub gas_limit: u64,
+    pub base_fee_per_gas: String,
+    pub timestamp: u64,
+    #[persistable(sortable)]
+    pub transactions_count: usize,
+    pub extra_data: String,
+}
+
+/// The slot of the canonical block which included an execution block, by execution block number
+#[derive(Persistable, Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[persistable(model = "default")]
+#[persistable(prefix = "/execution_payloads/n")]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi))]
+pub struct ExecutionBlockNumberModel {
+    pub slot: u64,
+}
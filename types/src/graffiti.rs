@@ -0,0 +1,120 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use indexer_macro::Persistable;
+use serde::Deserialize;
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+
+/// How many bits of the graffiti hash select its shard
+const SHARD_BITS: u32 = 12;
+
+/// The client reported for graffiti which don't mention any
+pub const UNKNOWN_CLIENT: &str = "Unknown";
+
+/// Consensus clients by the code they use in client version graffiti, such as `GE3f2aLH9c4e`
+const CLIENT_CODES: [(&str, &str); 6] = [
+    ("GR", "Grandine"),
+    ("LH", "Lighthouse"),
+    ("LS", "Lodestar"),
+    ("NB", "Nimbus"),
+    ("PM", "Prysm"),
+    ("TK", "Teku"),
+];
+
+/// The UTF-8 text of raw graffiti, without its zero padding.
+/// Empty graffiti and graffiti which aren't valid UTF-8 have no text.
+pub fn decode(bytes: &[u8]) -> Option<String> {
+    let end = bytes.iter().rposition(|byte| *byte != 0)?;
+
+    std::str::from_utf8(&bytes[..=end])
+        .ok()
+        .map(|text| text.to_string())
+}
+
+/// Trims and lowercases graffiti, so that lookups are case insensitive
+pub fn normalize(graffiti: &str) -> String {
+    graffiti.trim().to_lowercase()
+}
+
+/// The shard of graffiti, from the FNV-1a hash of its normalized text
+pub fn shard_id(graffiti: &str) -> String {
+    let hash = normalize(graffiti)
+        .bytes()
+        .fold(0x811c9dc5u32, |hash, byte| {
+            (hash ^ byte as u32).wrapping_mul(0x01000193)
+        });
+
+    format!("{:03x}", hash >> (32 - SHARD_BITS))
+}
+
+/// The consensus client which proposed a block, from its client version code or its name
+pub fn client(graffiti: &str) -> Option<&'static str> {
+    client_from_version(graffiti).or_else(|| {
+        let graffiti = graffiti.to_lowercase();
+
+        CLIENT_CODES
+            .iter()
+            .map(|(_, name)| *name)
+            .find(|name| graffiti.contains(&name.to_lowercase()))
+    })
+}
+
+/// The execution client code and its commit come first, then the consensus client code
+fn client_from_version(graffiti: &str) -> Option<&'static str> {
+    let is_code = |code: &[u8]| code.iter().all(u8::is_ascii_uppercase);
+    let bytes = graffiti.as_bytes();
+
+    if bytes.len() < 4 || !is_code(&bytes[..2]) {
+        return None;
+    }
+
+    let commit_len = bytes[2..]
+        .iter()
+        .take_while(|byte| byte.is_ascii_digit() || (b'a'..=b'f').contains(byte))
+        .count();
+    let code = bytes.get(2 + commit_len..4 + commit_len)?;
+
+    CLIENT_CODES
+        .iter()
+        .find(|(client_code, _)| client_code.as_bytes() == code)
+        .map(|(_, name)| *name)
+}
+
+/// The slots of the canonical blocks by normalized graffiti, for the graffiti of a shard
+#[derive(Persistable, Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[persistable(id = "String")]
+#[persistable(model = "default")]
+#[persistable(prefix = "/blocks/g")]
+#[serde(rename_all = "camelCase")]
+pub struct GraffitiShardModel {
+    pub slots: BTreeMap<String, BTreeSet<u64>>,
+}
+
+/// How many canonical blocks of an epoch each client and each graffiti proposed
+#[derive(Persistable, Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[persistable(model = "default")]
+#[persistable(prefix = "/epochs/g")]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi))]
+#[serde(rename_all = "camelCase")]
+pub struct GraffitiTallyModel {
+    pub clients: BTreeMap<String, usize>,
+    pub graffiti: BTreeMap<String, usize>,
+}
+
+impl GraffitiTallyModel {
+    pub fn record(&mut self, graffiti: Option<&str>) {
+        let graffiti = graffiti
+            .map(str::trim)
+            .filter(|graffiti| !graffiti.is_empty());
+        let client = graffiti.and_then(client).unwrap_or(UNKNOWN_CLIENT);
+
+        *self.clients.entry(client.to_string()).or_default() += 1;
+
+        if let Some(graffiti) = graffiti {
+            *self.graffiti.entry(graffiti.to_string()).or_default() += 1;
+        }
+    }
+}
@@ -1,18 +1,28 @@
 pub mod attestation;
+pub mod attestation_performance;
 pub mod block;
 pub mod block_request;
 pub mod block_root;
 pub mod committee;
 pub mod deposit;
 pub mod epoch;
+pub mod execution_payload;
 pub mod good_peer;
+pub mod graffiti;
 pub mod meta;
 pub mod model;
 pub mod path;
 pub mod persistable;
+pub mod search;
+pub mod slashing;
+pub mod sync_committee;
 pub mod utils;
 pub mod validator;
+pub mod validator_balances;
+pub mod validator_lookup;
+pub mod voluntary_exit;
 pub mod vote;
+pub mod withdrawal;
 
 pub use serde::de::DeserializeOwned;
 pub use serde::Serialize;
@@ -1,4 +1,4 @@
-use std::convert::TryFrom;
+use std::{collections::BTreeMap, convert::TryFrom, ops::Range};
 
 use serde::{Deserialize, Serialize};
 
@@ -10,12 +10,22 @@ use crate::{
 #[cfg(feature = "wasm")]
 use tsify::Tsify;
 
+/// How many ids a chunk of a sorted index holds, when its meta doesn't tell
+pub const DEFAULT_SORTABLE_CHUNK_SIZE: usize = 10;
+
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[cfg_attr(feature = "wasm", derive(Tsify))]
 #[cfg_attr(feature = "wasm", tsify(into_wasm_abi))]
 pub struct Meta {
     pub count: usize,
     pub specific: MetaSpecific,
+    /// The chunk sizes of the sorted indexes, by sortable field
+    #[serde(default)]
+    pub sortable_chunk_sizes: BTreeMap<String, usize>,
+    /// How many ids the sorted indexes hold, by sortable field. Fewer than `count` when the
+    /// models before a checkpoint haven't been indexed.
+    #[serde(default)]
+    pub sortable_lengths: BTreeMap<String, usize>,
 }
 
 impl Meta {
@@ -23,9 +33,54 @@ impl Meta {
         Self {
             count: 0,
             specific: MetaSpecific::Deposit(DepositMeta::default()),
+            ..Default::default()
+        }
+    }
+
+    pub fn partial(count: usize, not_indexed: Range<u64>) -> Self {
+        Self {
+            count,
+            specific: MetaSpecific::Partial(PartialMeta {
+                not_indexed_start: not_indexed.start,
+                not_indexed_end: not_indexed.end,
+            }),
+            ..Default::default()
         }
     }
 
+    pub fn not_indexed(&self) -> Option<Range<u64>> {
+        match &self.specific {
+            MetaSpecific::Partial(p) if p.not_indexed_start < p.not_indexed_end => {
+                Some(p.not_indexed_start..p.not_indexed_end)
+            }
+            _ => None,
+        }
+    }
+
+    /// Flags the model `id` as indexed, growing the count if needed
+    pub fn mark_indexed(&mut self, id: u64) {
+        self.count = self.count.max(id as usize + 1);
+
+        if let MetaSpecific::Partial(p) = &mut self.specific {
+            if (p.not_indexed_start..p.not_indexed_end).contains(&id) {
+                p.not_indexed_start = id + 1;
+            }
+        }
+    }
+
+    /// How many ids each chunk of the sorted index of `field` holds
+    pub fn sortable_chunk_size(&self, field: &str) -> usize {
+        self.sortable_chunk_sizes
+            .get(field)
+            .copied()
+            .unwrap_or(DEFAULT_SORTABLE_CHUNK_SIZE)
+    }
+
+    /// How many ids the sorted index of `field` holds, when recorded
+    pub fn sortable_length(&self, field: &str) -> Option<usize> {
+        self.sortable_lengths.get(field).copied()
+    }
+
     pub fn to_path<M: Prefix>(base_path: &str) -> String {
         format!("{}{}/meta.msg", base_path, M::prefix())
     }
@@ -40,11 +95,20 @@ pub struct DepositMeta {
     pub latest_block: Option<u64>,
 }
 
+/// Models whose ids are within `not_indexed_start..not_indexed_end` haven't been indexed,
+/// e.g. when indexing started from a checkpoint instead of genesis.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct PartialMeta {
+    pub not_indexed_start: u64,
+    pub not_indexed_end: u64,
+}
+
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub enum MetaSpecific {
     #[default]
     Empty,
     Deposit(DepositMeta),
+    Partial(PartialMeta),
 }
 
 impl<'a> TryFrom<&'a Meta> for &'a DepositMeta {
@@ -52,8 +116,8 @@ impl<'a> TryFrom<&'a Meta> for &'a DepositMeta {
 
     fn try_from(value: &'a Meta) -> Result<Self, Self::Error> {
         match &value.specific {
-            MetaSpecific::Empty => Err("Invalid meta type".to_string()),
             MetaSpecific::Deposit(d) => Ok(d),
+            _ => Err("Invalid meta type".to_string()),
         }
     }
 }
@@ -63,8 +127,8 @@ impl<'a> TryFrom<&'a mut Meta> for &'a mut DepositMeta {
 
     fn try_from(value: &'a mut Meta) -> Result<Self, Self::Error> {
         match &mut value.specific {
-            MetaSpecific::Empty => Err("Invalid meta type".to_string()),
             MetaSpecific::Deposit(d) => Ok(d),
+            _ => Err("Invalid meta type".to_string()),
         }
     }
 }
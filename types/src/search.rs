@@ -0,0 +1,87 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use indexer_macro::Persistable;
+use serde::Deserialize;
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+
+use crate::validator_lookup::normalize;
+
+/// How many leading characters of a key select its shard
+const SHARD_CHARS: usize = 3;
+
+/// Graffiti words shorter than this aren't indexed
+const MIN_TOKEN_LEN: usize = 3;
+
+/// The page a search key resolves to
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[serde(tag = "kind", content = "id", rename_all = "camelCase")]
+pub enum SearchResult {
+    Block(u64),
+    Epoch(u64),
+    Validator(u64),
+    Deposit(u64),
+}
+
+/// The key of a hex encoded value, such as a root or a public key
+pub fn hex_key(value: &str) -> String {
+    normalize(value)
+}
+
+/// The keys of the words of a graffiti, lowercased
+pub fn text_keys(text: &str) -> BTreeSet<String> {
+    text.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| word.len() >= MIN_TOKEN_LEN)
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+/// The shard of a key, or of the query looking it up
+pub fn shard_id(key: &str) -> String {
+    key.strip_prefix("0x")
+        .unwrap_or(key)
+        .chars()
+        .take(SHARD_CHARS)
+        .collect()
+}
+
+/// Search results by key, for the keys of a shard
+#[derive(Persistable, Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[persistable(id = "String")]
+#[persistable(model = "default")]
+#[persistable(prefix = "/search")]
+#[serde(rename_all = "camelCase")]
+pub struct SearchShardModel {
+    pub entries: BTreeMap<String, BTreeSet<SearchResult>>,
+}
+
+impl SearchShardModel {
+    /// The results of the keys starting with `query`, those of the exact match first
+    pub fn find(&self, query: &str, limit: usize) -> Vec<SearchResult> {
+        let prefixed = self
+            .entries
+            .range(query.to_string()..)
+            .take_while(|(key, _)| key.starts_with(query))
+            .filter(|(key, _)| key.as_str() != query)
+            .flat_map(|(_, results)| results.iter());
+
+        let mut results: Vec<SearchResult> = vec![];
+
+        self.entries
+            .get(query)
+            .into_iter()
+            .flatten()
+            .chain(prefixed)
+            .for_each(|result| {
+                if !results.contains(result) {
+                    results.push(*result);
+                }
+            });
+
+        results.truncate(limit);
+        results
+    }
+}
@@ -0,0 +1,164 @@
+use indexer_macro::Persistable;
+use serde::Deserialize;
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[serde(rename_all = "camelCase")]
+pub struct SignedBlockHeaderModel {
+    pub slot: u64,
+    pub proposer_index: u64,
+    pub parent_root: String,
+    pub state_root: String,
+    pub body_root: String,
+    pub signature: String,
+}
+
+#[cfg(feature = "indexing")]
+impl From<&lighthouse_types::SignedBeaconBlockHeader> for SignedBlockHeaderModel {
+    fn from(value: &lighthouse_types::SignedBeaconBlockHeader) -> Self {
+        SignedBlockHeaderModel {
+            slot: value.message.slot.as_u64(),
+            proposer_index: value.message.proposer_index,
+            parent_root: format!("{:?}", value.message.parent_root),
+            state_root: format!("{:?}", value.message.state_root),
+            body_root: format!("{:?}", value.message.body_root),
+            signature: value.signature.to_string(),
+        }
+    }
+}
+
+/// Two conflicting headers signed by the same proposer, included in a block
+#[derive(Persistable, Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[persistable(model = "collection")]
+#[persistable(prefix = "/blocks/ps")]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi))]
+#[serde(rename_all = "camelCase")]
+pub struct ProposerSlashingModel {
+    pub proposer_index: u64,
+    pub signed_header_1: SignedBlockHeaderModel,
+    pub signed_header_2: SignedBlockHeaderModel,
+}
+
+#[cfg(feature = "indexing")]
+impl From<&lighthouse_types::ProposerSlashing> for ProposerSlashingModel {
+    fn from(value: &lighthouse_types::ProposerSlashing) -> Self {
+        ProposerSlashingModel {
+            proposer_index: value.signed_header_1.message.proposer_index,
+            signed_header_1: SignedBlockHeaderModel::from(&value.signed_header_1),
+            signed_header_2: SignedBlockHeaderModel::from(&value.signed_header_2),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedAttestationModel {
+    pub attesting_indices: Vec<u64>,
+    pub slot: u64,
+    pub committee_index: u64,
+    pub beacon_block_root: String,
+    pub source_epoch: u64,
+    pub source_root: String,
+    pub target_epoch: u64,
+    pub target_root: String,
+    pub signature: String,
+}
+
+#[cfg(feature = "indexing")]
+impl<T: lighthouse_types::EthSpec> From<&lighthouse_types::IndexedAttestation<T>>
+    for IndexedAttestationModel
+{
+    fn from(value: &lighthouse_types::IndexedAttestation<T>) -> Self {
+        IndexedAttestationModel {
+            attesting_indices: value.attesting_indices.to_vec(),
+            slot: value.data.slot.as_u64(),
+            committee_index: value.data.index,
+            beacon_block_root: format!("{:?}", value.data.beacon_block_root),
+            source_epoch: value.data.source.epoch.as_u64(),
+            source_root: format!("{:?}", value.data.source.root),
+            target_epoch: value.data.target.epoch.as_u64(),
+            target_root: format!("{:?}", value.data.target.root),
+            signature: value.signature.to_string(),
+        }
+    }
+}
+
+/// Two conflicting attestations, included in a block
+#[derive(Persistable, Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[persistable(model = "collection")]
+#[persistable(prefix = "/blocks/as")]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi))]
+#[serde(rename_all = "camelCase")]
+pub struct AttesterSlashingModel {
+    /// The validators which attested in both attestations
+    pub slashed_indices: Vec<u64>,
+    pub attestation_1: IndexedAttestationModel,
+    pub attestation_2: IndexedAttestationModel,
+}
+
+#[cfg(feature = "indexing")]
+impl<T: lighthouse_types::EthSpec> From<&lighthouse_types::AttesterSlashing<T>>
+    for AttesterSlashingModel
+{
+    fn from(value: &lighthouse_types::AttesterSlashing<T>) -> Self {
+        let attestation_1 = IndexedAttestationModel::from(&value.attestation_1);
+        let attestation_2 = IndexedAttestationModel::from(&value.attestation_2);
+
+        AttesterSlashingModel {
+            slashed_indices: attestation_1
+                .attesting_indices
+                .iter()
+                .filter(|index| attestation_2.attesting_indices.contains(index))
+                .copied()
+                .collect(),
+            attestation_1,
+            attestation_2,
+        }
+    }
+}
+
+/// Every slashing of the chain, in inclusion order
+#[derive(Persistable, Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[persistable(model = "default")]
+#[persistable(prefix = "/slashings")]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi))]
+#[serde(rename_all = "camelCase")]
+pub struct SlashingModel {
+    pub slot: u64,
+    /// Either `proposer` or `attester`
+    pub kind: String,
+    pub slashed_indices: Vec<u64>,
+    #[persistable(sortable)]
+    pub slashed_validators_count: usize,
+    /// The proposer of the block, which gets the whistleblower reward
+    pub whistleblower: u64,
+}
+
+impl SlashingModel {
+    pub fn proposer(slot: u64, whistleblower: u64, slashing: &ProposerSlashingModel) -> Self {
+        SlashingModel {
+            slot,
+            kind: "proposer".to_string(),
+            slashed_indices: vec![slashing.proposer_index],
+            slashed_validators_count: 1,
+            whistleblower,
+        }
+    }
+
+    pub fn attester(slot: u64, whistleblower: u64, slashing: &AttesterSlashingModel) -> Self {
+        SlashingModel {
+            slot,
+            kind: "attester".to_string(),
+            slashed_indices: slashing.slashed_indices.clone(),
+            slashed_validators_count: slashing.slashed_indices.len(),
+            whistleblower,
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use indexer_macro::Persistable;
+use serde::Deserialize;
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+
+/// The members of the sync committee of a period, by validator index
+#[derive(Persistable, Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[persistable(model = "default")]
+#[persistable(prefix = "/sync_committees")]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi))]
+#[serde(rename_all = "camelCase")]
+pub struct SyncCommitteeModel {
+    pub validators: Vec<u64>,
+}
+
+/// The sync committee members who signed the parent block root, in a block
+#[derive(Persistable, Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[persistable(model = "option")]
+#[persistable(prefix = "/blocks/s")]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi))]
+#[serde(rename_all = "camelCase")]
+pub struct SyncAggregateModel {
+    pub period: u64,
+    pub committee_bits: Vec<bool>,
+    pub participants: Vec<u64>,
+    pub signature: String,
+}
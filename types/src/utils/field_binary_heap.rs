@@ -1,16 +1,19 @@
-use std::{collections::BinaryHeap, fs::File, io::BufWriter};
+use std::{collections::BinaryHeap, hash::Hash};
 
-use rmp_serde::Serializer;
 use serde::{de::DeserializeOwned, Serialize};
 
-use super::Orderable;
+use super::{Orderable, SortableRun};
 
 #[derive(Default)]
 pub struct FieldBinaryHeap<Id: Clone + Ord + Serialize + DeserializeOwned, F: Ord> {
     inner: BinaryHeap<Orderable<Id, F>>,
 }
 
-impl<Id: Clone + Ord + Serialize + DeserializeOwned, F: Ord> FieldBinaryHeap<Id, F> {
+impl<Id, F> FieldBinaryHeap<Id, F>
+where
+    Id: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned,
+    F: Clone + Ord + Serialize + DeserializeOwned,
+{
     pub fn new() -> Self {
         FieldBinaryHeap {
             inner: BinaryHeap::new(),
@@ -27,17 +30,21 @@ impl<Id: Clone + Ord + Serialize + DeserializeOwned, F: Ord> FieldBinaryHeap<Id,
         self.inner.push(item);
     }
 
-    pub fn persist(self, base_dir: &str, field_name: &str) -> Result<(), String> {
-        for (i, chunk) in self.inner.into_sorted_vec().chunks(10).enumerate() {
-            let indexes: Vec<Id> = chunk.iter().map(|x| x.id.clone()).collect();
-            let path = format!("{}/s/{}/{}.msg", base_dir, field_name, i + 1);
-            let mut f = BufWriter::new(
-                File::create(&path).map_err(|_| format!("File not found: {}", path))?,
-            );
-            indexes
-                .serialize(&mut Serializer::new(&mut f))
-                .map_err(|err| err.to_string())?;
-        }
-        Ok(())
+    /// Writes the ids in ascending order, `chunk_size` ids per file, the first file being `1.msg`,
+    /// along with the run later merges start from
+    pub fn persist(
+        self,
+        base_dir: &str,
+        field_name: &str,
+        chunk_size: usize,
+    ) -> Result<(), String> {
+        let entries = self
+            .inner
+            .into_sorted_vec()
+            .into_iter()
+            .map(|orderable| (orderable.ordering, orderable.id))
+            .collect();
+
+        SortableRun::from_sorted(entries).persist(base_dir, field_name, chunk_size)
     }
 }
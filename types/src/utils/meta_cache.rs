@@ -87,6 +87,19 @@ impl MetaCache {
             Entry::Vacant(VacantEntry::new(self))
         }
     }
+
+    /// Same as `entry`, but loads the meta from its file when it isn't cached yet,
+    /// so that the range not indexed yet of a partial meta survives a restart
+    pub fn loaded_entry<M>(&mut self) -> Entry<'_, M>
+    where
+        M: Prefix,
+    {
+        if self.get_mut::<M>().is_some() {
+            Entry::Occupied(OccupiedEntry::new(self))
+        } else {
+            Entry::Vacant(VacantEntry::new(self))
+        }
+    }
 }
 
 pub enum Entry<'a, M>
@@ -153,6 +166,17 @@ where
 
         meta
     }
+
+    pub fn mark_indexed(self, id: u64) -> &'a mut Meta {
+        let meta = match self {
+            Entry::Occupied(o) => o.into_mut(),
+            Entry::Vacant(v) => v.insert(Meta::default()),
+        };
+
+        meta.mark_indexed(id);
+
+        meta
+    }
 }
 
 pub struct OccupiedEntry<'a, M>
@@ -10,6 +10,7 @@ mod orderable;
 mod persistable_cache;
 #[cfg(feature = "indexing")]
 mod request_attempts;
+mod sortable_run;
 
 #[cfg(feature = "indexing")]
 pub use block_by_root_request_state::BlockByRootRequestState;
@@ -23,3 +24,4 @@ pub use orderable::Orderable;
 pub use persistable_cache::PersistableCache;
 #[cfg(feature = "indexing")]
 pub use request_attempts::RequestAttempts;
+pub use sortable_run::{PendingSortable, SortableRun};
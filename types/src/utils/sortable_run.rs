@@ -0,0 +1,359 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fs::{self, File},
+    hash::Hash,
+    io::{BufWriter, ErrorKind},
+};
+
+use rmp_serde::Serializer;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    meta::Meta,
+    persistable::{MsgPackDeserializable, MsgPackSerializable},
+};
+
+/// The orderables registered for the sorted indexes of a model, generated by the `Persistable`
+/// derive for the models with sortable fields
+pub trait PendingSortable: Default {
+    /// Merges the orderables into the sorted indexes, in chunks of the size recorded in `meta`.
+    /// Returns how many ids each merged index holds, by field.
+    fn merge(&self, base_dir: &str, meta: &Meta) -> Result<BTreeMap<String, usize>, String>;
+
+    /// Registers back the orderables of a failed merge into `pending`, unless they've been
+    /// registered again meanwhile
+    fn requeue(self, pending: &mut Self);
+}
+
+/// The entries of a sorted index along with their ordering, in ascending order, so that newly
+/// persisted models can be merged in without reading the other models again.
+/// It's written next to the chunks of the index, which are derived from it.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SortableRun<Id, F> {
+    entries: Vec<(F, Id)>,
+}
+
+impl<Id, F> MsgPackSerializable for SortableRun<Id, F>
+where
+    Id: Serialize,
+    F: Serialize,
+{
+}
+
+impl<Id, F> MsgPackDeserializable for SortableRun<Id, F>
+where
+    Id: DeserializeOwned,
+    F: DeserializeOwned,
+{
+}
+
+impl<Id, F> SortableRun<Id, F>
+where
+    Id: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned,
+    F: Clone + Ord + Serialize + DeserializeOwned,
+{
+    /// A run of entries already sorted by ordering, then by id
+    pub fn from_sorted(entries: Vec<(F, Id)>) -> Self {
+        SortableRun { entries }
+    }
+
+    pub fn path(prefixed_dir: &str, field_name: &str) -> String {
+        format!("{}/s/{}/run.msg", prefixed_dir, field_name)
+    }
+
+    /// Merges `pending` into the run of the field and rewrites the chunks which changed.
+    /// An entry of `pending` replaces the one of the run with the same id, and the entries of
+    /// the `removed` ids are dropped. The run of an index built before runs existed is rebuilt
+    /// from its chunks, `ordering` looking the ordering of their ids up.
+    /// Returns how many entries the run holds.
+    pub fn merge(
+        prefixed_dir: &str,
+        field_name: &str,
+        pending: &BTreeMap<Id, F>,
+        removed: &BTreeSet<Id>,
+        chunk_size: usize,
+        ordering: impl Fn(&Id) -> Result<F, String>,
+    ) -> Result<usize, String> {
+        let path = Self::path(prefixed_dir, field_name);
+        let run = match Self::deserialize_from_file(&path) {
+            Ok(run) => run,
+            Err(_) => Self::from_chunks(prefixed_dir, field_name, ordering)?,
+        };
+
+        if pending.is_empty() && removed.is_empty() {
+            return Ok(run.entries.len());
+        }
+
+        let previous_ids = run.ids().cloned().collect::<Vec<_>>();
+        let run = run.merged(pending, removed);
+
+        run.persist_chunks(prefixed_dir, field_name, chunk_size, &previous_ids)?;
+        run.serialize_to_file(&path)?;
+
+        Ok(run.entries.len())
+    }
+
+    /// Writes the run and every chunk derived from it
+    pub fn persist(
+        &self,
+        prefixed_dir: &str,
+        field_name: &str,
+        chunk_size: usize,
+    ) -> Result<(), String> {
+        self.persist_chunks(prefixed_dir, field_name, chunk_size, &[])?;
+        self.serialize_to_file(&Self::path(prefixed_dir, field_name))
+    }
+
+    fn ids(&self) -> impl Iterator<Item = &Id> {
+        self.entries.iter().map(|(_, id)| id)
+    }
+
+    /// Reads the ids of the chunks of the field, if any, along with their ordering
+    fn from_chunks(
+        prefixed_dir: &str,
+        field_name: &str,
+        ordering: impl Fn(&Id) -> Result<F, String>,
+    ) -> Result<Self, String> {
+        let mut entries = vec![];
+
+        for number in 1.. {
+            let path = chunk_path(prefixed_dir, field_name, number);
+            let file = match File::open(&path) {
+                Ok(file) => file,
+                Err(err) if err.kind() == ErrorKind::NotFound => break,
+                Err(err) => return Err(format!("Can't open '{path}': {err}")),
+            };
+
+            rmp_serde::from_read::<_, Vec<Id>>(file)
+                .map_err(|err| format!("Can't deserialize {path}: {err}"))?
+                .into_iter()
+                .try_for_each(|id| {
+                    entries.push((ordering(&id)?, id));
+                    Ok::<_, String>(())
+                })?;
+        }
+
+        entries.sort();
+
+        Ok(SortableRun { entries })
+    }
+
+    fn merged(self, pending: &BTreeMap<Id, F>, removed: &BTreeSet<Id>) -> Self {
+        let replaced = pending.keys().collect::<HashSet<_>>();
+
+        let mut pending = pending
+            .iter()
+            .map(|(id, ordering)| (ordering.clone(), id.clone()))
+            .collect::<Vec<_>>();
+        pending.sort();
+
+        let mut kept = self
+            .entries
+            .into_iter()
+            .filter(|(_, id)| !replaced.contains(id) && !removed.contains(id))
+            .peekable();
+        let mut pending = pending.into_iter().peekable();
+        let mut entries = Vec::with_capacity(kept.size_hint().0 + pending.size_hint().0);
+
+        loop {
+            let entry = match (kept.peek(), pending.peek()) {
+                (Some(k), Some(p)) if k <= p => kept.next(),
+                (Some(_), Some(_)) => pending.next(),
+                (Some(_), None) => kept.next(),
+                (None, Some(_)) => pending.next(),
+                (None, None) => break,
+            };
+
+            entries.extend(entry);
+        }
+
+        SortableRun { entries }
+    }
+
+    /// Writes the chunks whose ids differ from the ones of `previous_ids` at the same position,
+    /// and removes the ones past the end of the run
+    fn persist_chunks(
+        &self,
+        prefixed_dir: &str,
+        field_name: &str,
+        chunk_size: usize,
+        previous_ids: &[Id],
+    ) -> Result<(), String> {
+        let ids = self.ids().cloned().collect::<Vec<_>>();
+        let mut previous_chunks = previous_ids.chunks(chunk_size);
+
+        for (i, chunk) in ids.chunks(chunk_size).enumerate() {
+            if previous_chunks.next() == Some(chunk) {
+                continue;
+            }
+
+            let path = chunk_path(prefixed_dir, field_name, i + 1);
+            let mut f = BufWriter::new(
+                File::create(&path).map_err(|_| format!("File not found: {}", path))?,
+            );
+            chunk
+                .serialize(&mut Serializer::new(&mut f))
+                .map_err(|err| err.to_string())?;
+        }
+
+        let chunks_count = ids.len().div_ceil(chunk_size);
+
+        (chunks_count..previous_chunks.len() + chunks_count).try_for_each(|i| {
+            let path = chunk_path(prefixed_dir, field_name, i + 1);
+
+            fs::remove_file(&path).map_err(|err| format!("Failed to remove '{path}': {err}"))
+        })
+    }
+}
+
+fn chunk_path(prefixed_dir: &str, field_name: &str, number: usize) -> String {
+    format!("{}/s/{}/{}.msg", prefixed_dir, field_name, number)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    type Run = SortableRun<u64, u64>;
+
+    fn prefixed_dir(name: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("sortable_run_{name}_{}", std::process::id()));
+
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("s/field")).unwrap();
+
+        dir.to_string_lossy().into_owned()
+    }
+
+    fn read_chunk(prefixed_dir: &str, number: usize) -> Vec<u64> {
+        let file = File::open(chunk_path(prefixed_dir, "field", number)).unwrap();
+        rmp_serde::from_read(file).unwrap()
+    }
+
+    fn no_ordering(_: &u64) -> Result<u64, String> {
+        Err("The run should exist".to_string())
+    }
+
+    #[test]
+    fn merged_replaces_entries_of_the_same_id() {
+        let run = Run::from_sorted(vec![(1, 10), (2, 11), (3, 12)]);
+        let pending = BTreeMap::from([(11, 5)]);
+
+        assert_eq!(
+            run.merged(&pending, &BTreeSet::new()).entries,
+            vec![(1, 10), (3, 12), (5, 11)]
+        );
+    }
+
+    #[test]
+    fn merged_orders_ties_by_id() {
+        let run = Run::from_sorted(vec![(1, 10), (1, 12)]);
+        let pending = BTreeMap::from([(11, 1), (9, 1)]);
+
+        assert_eq!(
+            run.merged(&pending, &BTreeSet::new()).entries,
+            vec![(1, 9), (1, 10), (1, 11), (1, 12)]
+        );
+    }
+
+    #[test]
+    fn merged_into_empty_run() {
+        let pending = BTreeMap::from([(2, 3), (1, 3), (3, 1)]);
+
+        assert_eq!(
+            Run::from_sorted(vec![])
+                .merged(&pending, &BTreeSet::new())
+                .entries,
+            vec![(1, 3), (3, 1), (3, 2)]
+        );
+    }
+
+    #[test]
+    fn merged_drops_removed_ids() {
+        let run = Run::from_sorted(vec![(1, 10), (2, 11), (3, 12)]);
+        let removed = BTreeSet::from([11, 13]);
+
+        assert_eq!(
+            run.merged(&BTreeMap::new(), &removed).entries,
+            vec![(1, 10), (3, 12)]
+        );
+    }
+
+    #[test]
+    fn persist_chunks_rewrites_only_changed_chunks() {
+        let prefixed_dir = prefixed_dir("changed_chunks");
+
+        Run::from_sorted(vec![(1, 10), (2, 11), (3, 12), (4, 13), (5, 14)])
+            .persist(&prefixed_dir, "field", 2)
+            .unwrap();
+
+        // Marks the first chunk, which an entry sorted after it must leave untouched
+        fs::write(chunk_path(&prefixed_dir, "field", 1), b"untouched").unwrap();
+
+        let pending = BTreeMap::from([(15, 6), (12, 7)]);
+        let length = Run::merge(
+            &prefixed_dir,
+            "field",
+            &pending,
+            &BTreeSet::new(),
+            2,
+            no_ordering,
+        )
+        .unwrap();
+
+        assert_eq!(length, 6);
+
+        assert_eq!(
+            fs::read(chunk_path(&prefixed_dir, "field", 1)).unwrap(),
+            b"untouched"
+        );
+        assert_eq!(read_chunk(&prefixed_dir, 2), vec![13, 14]);
+        assert_eq!(read_chunk(&prefixed_dir, 3), vec![15, 12]);
+
+        // Removing entries drops the chunks past the end of the run
+        Run::merge(
+            &prefixed_dir,
+            "field",
+            &BTreeMap::new(),
+            &BTreeSet::from([13, 14, 15, 12]),
+            2,
+            no_ordering,
+        )
+        .unwrap();
+
+        assert!(!Path::new(&chunk_path(&prefixed_dir, "field", 2)).exists());
+        assert!(!Path::new(&chunk_path(&prefixed_dir, "field", 3)).exists());
+    }
+
+    #[test]
+    fn merge_rebuilds_a_missing_run_from_the_chunks() {
+        let prefixed_dir = prefixed_dir("missing_run");
+        let orderings = BTreeMap::from([(10, 1), (11, 2), (12, 3)]);
+
+        Run::from_sorted(vec![(1, 10), (2, 11), (3, 12)])
+            .persist(&prefixed_dir, "field", 2)
+            .unwrap();
+        fs::remove_file(Run::path(&prefixed_dir, "field")).unwrap();
+
+        Run::merge(
+            &prefixed_dir,
+            "field",
+            &BTreeMap::from([(13, 0)]),
+            &BTreeSet::new(),
+            2,
+            |id| Ok(orderings[id]),
+        )
+        .unwrap();
+
+        assert_eq!(read_chunk(&prefixed_dir, 1), vec![13, 10]);
+        assert_eq!(read_chunk(&prefixed_dir, 2), vec![11, 12]);
+        assert_eq!(
+            Run::deserialize_from_file(&Run::path(&prefixed_dir, "field"))
+                .unwrap()
+                .entries,
+            vec![(0, 13), (1, 10), (2, 11), (3, 12)]
+        );
+    }
+}
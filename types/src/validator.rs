@@ -1,10 +1,12 @@
 use indexer_macro::Persistable;
 use serde::Deserialize;
 use serde::Serialize;
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 #[cfg(feature = "wasm")]
 use tsify::Tsify;
 
+use crate::withdrawal::ValidatorWithdrawal;
+
 #[derive(Persistable, Serialize, Deserialize, Debug, Clone)]
 #[cfg_attr(feature = "wasm", derive(Tsify))]
 #[persistable(model = "default")]
@@ -22,9 +24,12 @@ pub struct ValidatorModel {
     pub exit_epoch: Option<u64>,
     pub withdrawable_epoch: Option<u64>,
     pub status: String,
+    /// Id of the slashing which hit the validator, in the global slashings list
+    #[serde(default)]
+    pub slashing: Option<u64>,
 }
 
-#[derive(Persistable, Serialize, Deserialize, Debug, Clone)]
+#[derive(Persistable, Serialize, Deserialize, Debug, Clone, Default)]
 #[cfg_attr(feature = "wasm", derive(Tsify))]
 #[persistable(model = "default")]
 #[persistable(prefix = "/validators/e")]
@@ -33,4 +38,16 @@ pub struct ValidatorModel {
 
 pub struct ValidatorExtendedModel {
     pub execution_layer_deposits: HashSet<u64>,
+    /// Slots where the validator was the proposer and its block is canonical
+    #[serde(default)]
+    pub proposed_slots: BTreeSet<u64>,
+    /// Slots where the validator was the proposer but no canonical block has been proposed
+    #[serde(default)]
+    pub missed_slots: BTreeSet<u64>,
+    /// Withdrawals of canonical blocks, from the oldest to the newest
+    #[serde(default)]
+    pub withdrawals: Vec<ValidatorWithdrawal>,
+    /// Slot of the canonical block which included the voluntary exit of the validator
+    #[serde(default)]
+    pub voluntary_exit_slot: Option<u64>,
 }
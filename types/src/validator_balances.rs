@@ -0,0 +1,161 @@
+use indexer_macro::Persistable;
+use serde::Deserialize;
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+
+/// How many epochs a chunk of balances covers
+pub const BALANCES_CHUNK_EPOCHS: u64 = 1024;
+
+/// The balances of a validator at the end of the epochs of a chunk, starting at `start_epoch`
+#[derive(Persistable, Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[persistable(id = "String")]
+#[persistable(model = "default")]
+#[persistable(prefix = "/validators/b")]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi))]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorBalancesModel {
+    pub start_epoch: u64,
+    pub balances: Vec<u64>,
+    pub effective_balances: Vec<u64>,
+}
+
+impl ValidatorBalancesModel {
+    /// Id of the chunk containing `epoch` for a validator
+    pub fn chunk_id(validator_index: u64, epoch: u64) -> String {
+        format!("{validator_index}_{}", epoch / BALANCES_CHUNK_EPOCHS)
+    }
+
+    /// Ids of the chunks covering `start_epoch..end_epoch` for a validator
+    pub fn chunk_ids(validator_index: u64, start_epoch: u64, end_epoch: u64) -> Vec<String> {
+        if start_epoch >= end_epoch {
+            return vec![];
+        }
+
+        (start_epoch / BALANCES_CHUNK_EPOCHS..=end_epoch.saturating_sub(1) / BALANCES_CHUNK_EPOCHS)
+            .map(|chunk| format!("{validator_index}_{chunk}"))
+            .collect()
+    }
+
+    /// Records the balances at `epoch`, replacing the ones of this epoch only, so that epochs can
+    /// be recorded in any order. Epochs which haven't been recorded keep the previous balances.
+    pub fn record(&mut self, epoch: u64, balance: u64, effective_balance: u64) {
+        if self.balances.is_empty() {
+            self.start_epoch = epoch;
+        }
+
+        if epoch < self.start_epoch {
+            let gap = (self.start_epoch - epoch) as usize;
+
+            self.balances
+                .splice(0..0, std::iter::repeat_n(balance, gap));
+            self.effective_balances
+                .splice(0..0, std::iter::repeat_n(effective_balance, gap));
+            self.start_epoch = epoch;
+
+            return;
+        }
+
+        let position = (epoch - self.start_epoch) as usize;
+
+        while self.balances.len() < position {
+            self.balances
+                .push(*self.balances.last().unwrap_or(&balance));
+            self.effective_balances
+                .push(*self.effective_balances.last().unwrap_or(&effective_balance));
+        }
+
+        if position < self.balances.len() {
+            self.balances[position] = balance;
+            self.effective_balances[position] = effective_balance;
+        } else {
+            self.balances.push(balance);
+            self.effective_balances.push(effective_balance);
+        }
+    }
+
+    /// The epochs along with their balance and effective balance
+    pub fn series(&self) -> impl Iterator<Item = (u64, u64, u64)> + '_ {
+        let start_epoch = self.start_epoch;
+
+        self.balances
+            .iter()
+            .zip(&self.effective_balances)
+            .enumerate()
+            .map(move |(i, (balance, effective_balance))| {
+                (start_epoch + i as u64, *balance, *effective_balance)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series(model: &ValidatorBalancesModel) -> Vec<(u64, u64, u64)> {
+        model.series().collect()
+    }
+
+    #[test]
+    fn record_in_order() {
+        let mut model = ValidatorBalancesModel::default();
+
+        model.record(10, 1, 1);
+        model.record(11, 2, 2);
+        model.record(13, 4, 3);
+
+        assert_eq!(
+            series(&model),
+            vec![(10, 1, 1), (11, 2, 2), (12, 2, 2), (13, 4, 3)]
+        );
+    }
+
+    #[test]
+    fn record_earlier_epoch_keeps_later_ones() {
+        let mut model = ValidatorBalancesModel::default();
+
+        model.record(12, 3, 3);
+        model.record(13, 4, 4);
+        model.record(10, 1, 1);
+
+        assert_eq!(
+            series(&model),
+            vec![(10, 1, 1), (11, 1, 1), (12, 3, 3), (13, 4, 4)]
+        );
+    }
+
+    #[test]
+    fn record_replayed_epoch_overwrites_only_its_balance() {
+        let mut model = ValidatorBalancesModel::default();
+
+        model.record(10, 1, 1);
+        model.record(11, 2, 2);
+        model.record(12, 3, 3);
+        model.record(11, 5, 4);
+
+        assert_eq!(series(&model), vec![(10, 1, 1), (11, 5, 4), (12, 3, 3)]);
+    }
+
+    #[test]
+    fn record_backfilled_epochs_in_reverse() {
+        let mut model = ValidatorBalancesModel::default();
+
+        model.record(20, 9, 9);
+        (15..20)
+            .rev()
+            .for_each(|epoch| model.record(epoch, epoch, epoch));
+
+        assert_eq!(
+            series(&model),
+            vec![
+                (15, 15, 15),
+                (16, 16, 16),
+                (17, 17, 17),
+                (18, 18, 18),
+                (19, 19, 19),
+                (20, 9, 9)
+            ]
+        );
+    }
+}
@@ -0,0 +1,58 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use indexer_macro::Persistable;
+use serde::Deserialize;
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+
+/// How many leading hex digits of a key select its shard
+const SHARD_DIGITS: usize = 3;
+
+/// The shard of a hex encoded key, with or without its `0x` prefix
+pub fn shard_id(key: &str) -> String {
+    normalize(key).chars().skip(2).take(SHARD_DIGITS).collect()
+}
+
+/// Lowercases a hex encoded key and prefixes it with `0x`
+pub fn normalize(key: &str) -> String {
+    let key = key.trim().to_lowercase();
+
+    match key.strip_prefix("0x") {
+        Some(_) => key,
+        None => format!("0x{key}"),
+    }
+}
+
+/// The execution address of withdrawal credentials, unless they are still BLS ones
+pub fn withdrawal_address(withdrawal_credentials: &str) -> Option<String> {
+    let withdrawal_credentials = normalize(withdrawal_credentials);
+
+    if withdrawal_credentials.len() != 66 || withdrawal_credentials.starts_with("0x00") {
+        return None;
+    }
+
+    Some(format!("0x{}", &withdrawal_credentials[26..]))
+}
+
+/// Validator indices by public key, for the public keys of a shard
+#[derive(Persistable, Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[persistable(id = "String")]
+#[persistable(model = "default")]
+#[persistable(prefix = "/validators/pk")]
+#[serde(rename_all = "camelCase")]
+pub struct PubkeyShardModel {
+    pub validators: BTreeMap<String, u64>,
+}
+
+/// Validator indices by withdrawal address, for the addresses of a shard
+#[derive(Persistable, Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[persistable(id = "String")]
+#[persistable(model = "default")]
+#[persistable(prefix = "/validators/wa")]
+#[serde(rename_all = "camelCase")]
+pub struct WithdrawalAddressShardModel {
+    pub validators: BTreeMap<String, BTreeSet<u64>>,
+}
@@ -0,0 +1,42 @@
+use indexer_macro::Persistable;
+use serde::Deserialize;
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+
+/// The voluntary exits included in a block
+#[derive(Persistable, Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[persistable(model = "collection")]
+#[persistable(prefix = "/blocks/x")]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi))]
+#[serde(rename_all = "camelCase")]
+pub struct VoluntaryExitModel {
+    pub validator_index: u64,
+    pub epoch: u64,
+    pub signature: String,
+}
+
+#[cfg(feature = "indexing")]
+impl From<&lighthouse_types::SignedVoluntaryExit> for VoluntaryExitModel {
+    fn from(value: &lighthouse_types::SignedVoluntaryExit) -> Self {
+        VoluntaryExitModel {
+            validator_index: value.message.validator_index,
+            epoch: value.message.epoch.as_u64(),
+            signature: value.signature.to_string(),
+        }
+    }
+}
+
+/// Every voluntary exit of the chain, in inclusion order
+#[derive(Persistable, Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[persistable(model = "default")]
+#[persistable(prefix = "/voluntary_exits")]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi))]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedVoluntaryExitModel {
+    pub slot: u64,
+    #[serde(flatten)]
+    pub voluntary_exit: VoluntaryExitModel,
+}
@@ -0,0 +1,79 @@
+use indexer_macro::Persistable;
+use serde::Deserialize;
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+
+/// The withdrawals of the execution payload of a block
+#[derive(Persistable, Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[persistable(model = "collection")]
+#[persistable(prefix = "/blocks/w")]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi))]
+#[serde(rename_all = "camelCase")]
+pub struct WithdrawalModel {
+    pub index: u64,
+    pub validator_index: u64,
+    pub address: String,
+    pub amount: u64,
+}
+
+#[cfg(feature = "indexing")]
+impl From<&lighthouse_types::Withdrawal> for WithdrawalModel {
+    fn from(value: &lighthouse_types::Withdrawal) -> Self {
+        WithdrawalModel {
+            index: value.index,
+            validator_index: value.validator_index,
+            address: format!("{:?}", value.address),
+            amount: value.amount,
+        }
+    }
+}
+
+/// The withdrawal credentials changes from BLS to an execution address included in a block
+#[derive(Persistable, Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[persistable(model = "collection")]
+#[persistable(prefix = "/blocks/b")]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi))]
+#[serde(rename_all = "camelCase")]
+pub struct BlsToExecutionChangeModel {
+    pub validator_index: u64,
+    pub from_bls_pubkey: String,
+    pub to_execution_address: String,
+    pub signature: String,
+}
+
+#[cfg(feature = "indexing")]
+impl From<&lighthouse_types::SignedBlsToExecutionChange> for BlsToExecutionChangeModel {
+    fn from(value: &lighthouse_types::SignedBlsToExecutionChange) -> Self {
+        BlsToExecutionChangeModel {
+            validator_index: value.message.validator_index,
+            from_bls_pubkey: value.message.from_bls_pubkey.to_string(),
+            to_execution_address: format!("{:?}", value.message.to_execution_address),
+            signature: value.signature.to_string(),
+        }
+    }
+}
+
+/// A withdrawal of a validator, along with the slot of the block which included it
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorWithdrawal {
+    pub slot: u64,
+    pub index: u64,
+    pub address: String,
+    pub amount: u64,
+}
+
+impl From<(u64, &WithdrawalModel)> for ValidatorWithdrawal {
+    fn from((slot, withdrawal): (u64, &WithdrawalModel)) -> Self {
+        ValidatorWithdrawal {
+            slot,
+            index: withdrawal.index,
+            address: withdrawal.address.clone(),
+            amount: withdrawal.amount,
+        }
+    }
+}
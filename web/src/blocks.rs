@@ -1,19 +1,31 @@
 use js_sys::{Array, ArrayBuffer};
 use types::attestation::AttestationModel;
 use types::block::{BlockExtendedModel, BlockModel};
+use types::block_root::BlockRootModel;
 use types::committee::CommitteeModel;
+use types::execution_payload::{ExecutionBlockNumberModel, ExecutionPayloadModel};
 use types::meta::Meta;
 use types::path::ToPath;
+use types::slashing::{AttesterSlashingModel, ProposerSlashingModel};
+use types::sync_committee::SyncAggregateModel;
+use types::validator_lookup::normalize;
+use types::voluntary_exit::VoluntaryExitModel;
 use types::vote::VoteModel;
+use types::withdrawal::{BlsToExecutionChangeModel, WithdrawalModel};
 use wasm_bindgen::prelude::*;
 
 use crate::app::App;
+use crate::fetcher::fetch;
 use crate::page::{get_paths, RangeInput};
 use crate::views::attestations::AttestationView;
 use crate::views::blocks::{BlockExtendedView, BlockPaths, BlockView};
 use crate::views::committees::CommitteeView;
 use crate::views::votes::VoteView;
-use crate::{deserialize, AttestationArray, CommitteeArray, PathArray, VoteArray};
+use crate::views::withdrawals::{BlsToExecutionChangeView, WithdrawalView};
+use crate::{
+    deserialize, AttestationArray, BlsToExecutionChangeArray, CommitteeArray, PathArray, VoteArray,
+    WithdrawalArray,
+};
 
 use crate::to_js;
 
@@ -31,9 +43,38 @@ pub fn get_block_paths(app: &App, slot: u64) -> BlockPaths {
         committees: CommitteeModel::to_path(&app.base_url(), &slot),
         votes: VoteModel::to_path(&app.base_url(), &slot),
         attestations: AttestationModel::to_path(&app.base_url(), &slot),
+        sync_aggregate: SyncAggregateModel::to_path(&app.base_url(), &slot),
+        execution_payload: ExecutionPayloadModel::to_path(&app.base_url(), &slot),
+        withdrawals: WithdrawalModel::to_path(&app.base_url(), &slot),
+        bls_to_execution_changes: BlsToExecutionChangeModel::to_path(&app.base_url(), &slot),
+        voluntary_exits: VoluntaryExitModel::to_path(&app.base_url(), &slot),
+        proposer_slashings: ProposerSlashingModel::to_path(&app.base_url(), &slot),
+        attester_slashings: AttesterSlashingModel::to_path(&app.base_url(), &slot),
     }
 }
 
+/// The slot of the block with this root, if it has been indexed
+#[wasm_bindgen(js_name = "getBlockByRoot")]
+pub async fn get_block_by_root(app: &App, root: String) -> Option<u64> {
+    let path = BlockRootModel::to_path(&app.base_url(), &normalize(&root));
+
+    fetch::<BlockRootModel>(path)
+        .await
+        .ok()
+        .map(|model| model.slot)
+}
+
+/// The slot of the canonical block which included this execution block, if it has been indexed
+#[wasm_bindgen(js_name = "getBlockByExecutionNumber")]
+pub async fn get_block_by_execution_number(app: &App, block_number: u64) -> Option<u64> {
+    let path = ExecutionBlockNumberModel::to_path(&app.base_url(), &block_number);
+
+    fetch::<ExecutionBlockNumberModel>(path)
+        .await
+        .ok()
+        .map(|model| model.slot)
+}
+
 #[wasm_bindgen(js_name = "getBlockRangePaths")]
 pub async fn get_block_range_paths(
     app: &App,
@@ -89,6 +130,30 @@ pub async fn get_attestations(
         .map_err(Into::into)
 }
 
+#[wasm_bindgen(js_name = "getWithdrawals")]
+pub async fn get_withdrawals(withdrawals_buffer: ArrayBuffer) -> Result<WithdrawalArray, JsValue> {
+    deserialize::<Vec<WithdrawalModel>>(withdrawals_buffer)?
+        .into_iter()
+        .map(WithdrawalView::from)
+        .map(|v| to_js(&v))
+        .collect::<Result<Array, _>>()
+        .map(|a| a.unchecked_into())
+        .map_err(Into::into)
+}
+
+#[wasm_bindgen(js_name = "getBlsToExecutionChanges")]
+pub async fn get_bls_to_execution_changes(
+    bls_to_execution_changes_buffer: ArrayBuffer,
+) -> Result<BlsToExecutionChangeArray, JsValue> {
+    deserialize::<Vec<BlsToExecutionChangeModel>>(bls_to_execution_changes_buffer)?
+        .into_iter()
+        .map(BlsToExecutionChangeView::from)
+        .map(|v| to_js(&v))
+        .collect::<Result<Array, _>>()
+        .map(|a| a.unchecked_into())
+        .map_err(Into::into)
+}
+
 #[wasm_bindgen(js_name = "getBlockMetaPath")]
 pub fn get_block_meta_path(app: &App) -> JsValue {
     Meta::to_path::<BlockModel>(&app.base_url()).into()
@@ -5,6 +5,7 @@ use crate::{deserialize, PathArray};
 
 use js_sys::{ArrayBuffer, JsString};
 use types::epoch::{EpochExtendedModel, EpochModel};
+use types::graffiti::GraffitiTallyModel;
 use types::meta::Meta;
 use types::path::ToPath;
 use wasm_bindgen::prelude::*;
@@ -20,6 +21,7 @@ pub fn get_epoch_paths(app: &App, epoch: u64) -> EpochPaths {
     EpochPaths {
         epoch: EpochModel::to_path(&app.base_url(), &epoch),
         epoch_extended: EpochExtendedModel::to_path(&app.base_url(), &epoch),
+        graffiti_tally: GraffitiTallyModel::to_path(&app.base_url(), &epoch),
     }
 }
 
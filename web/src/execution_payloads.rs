@@ -0,0 +1,33 @@
+use js_sys::{ArrayBuffer, JsString};
+use types::execution_payload::ExecutionPayloadModel;
+use types::meta::Meta;
+use wasm_bindgen::prelude::*;
+
+use crate::app::App;
+use crate::page::{get_paths, RangeInput};
+use crate::views::execution_payloads::ExecutionPayloadView;
+use crate::{deserialize, PathArray};
+
+#[wasm_bindgen(js_name = "getExecutionPayload")]
+pub fn get_execution_payload(
+    buffer: ArrayBuffer,
+    slot: u64,
+) -> Result<ExecutionPayloadView, JsValue> {
+    let model = deserialize::<ExecutionPayloadModel>(buffer)?;
+    Ok(ExecutionPayloadView::from((slot, model)))
+}
+
+/// Pre-Merge slots have no payload, so the range has to be sorted by a payload field rather than by slot
+#[wasm_bindgen(js_name = "getExecutionPayloadRangePaths")]
+pub async fn get_execution_payload_range_paths(
+    app: &App,
+    input: RangeInput,
+    total_count: usize,
+) -> Result<PathArray, JsValue> {
+    get_paths::<ExecutionPayloadModel>(app, input, total_count).await
+}
+
+#[wasm_bindgen(js_name = "getExecutionPayloadMetaPath")]
+pub fn get_execution_payload_meta_path(app: &App) -> JsString {
+    Meta::to_path::<ExecutionPayloadModel>(&app.base_url()).into()
+}
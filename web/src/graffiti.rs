@@ -0,0 +1,60 @@
+use futures::future::join_all;
+use js_sys::{Array, ArrayBuffer};
+use types::graffiti::{normalize, shard_id, GraffitiShardModel, GraffitiTallyModel};
+use types::path::ToPath;
+use wasm_bindgen::prelude::*;
+
+use crate::app::App;
+use crate::fetcher::fetch;
+use crate::views::graffiti::GraffitiTallyView;
+use crate::{deserialize, to_js, BigIntArray, GraffitiTallyArray};
+
+#[wasm_bindgen(js_name = "getGraffitiTally")]
+pub fn get_graffiti_tally(buffer: ArrayBuffer, epoch: u64) -> Result<GraffitiTallyView, JsValue> {
+    let model = deserialize::<GraffitiTallyModel>(buffer)?;
+    Ok(GraffitiTallyView::from((epoch, model)))
+}
+
+/// The client and graffiti tallies of the epochs `start_epoch..end_epoch`.
+/// Epochs which haven't been indexed are skipped.
+#[wasm_bindgen(js_name = "getGraffitiTallies")]
+pub async fn get_graffiti_tallies(
+    app: &App,
+    start_epoch: u64,
+    end_epoch: u64,
+) -> Result<GraffitiTallyArray, JsValue> {
+    let tallies = (start_epoch..end_epoch).map(|epoch| async move {
+        fetch::<GraffitiTallyModel>(GraffitiTallyModel::to_path(&app.base_url(), &epoch))
+            .await
+            .map(|model| (epoch, model))
+    });
+
+    join_all(tallies)
+        .await
+        .into_iter()
+        .filter_map(Result::ok)
+        .map(GraffitiTallyView::from)
+        .map(|v| to_js(&v))
+        .collect::<Result<Array, _>>()
+        .map(|a| a.unchecked_into())
+        .map_err(Into::into)
+}
+
+/// The slots of the canonical blocks with this graffiti, ignoring case,
+/// fetching only the shard it belongs to
+#[wasm_bindgen(js_name = "getBlocksByGraffiti")]
+pub async fn get_blocks_by_graffiti(app: &App, graffiti: String) -> BigIntArray {
+    let graffiti = normalize(&graffiti);
+    let path = GraffitiShardModel::to_path(&app.base_url(), &shard_id(&graffiti));
+
+    // A shard without any graffiti isn't written
+    fetch::<GraffitiShardModel>(path)
+        .await
+        .ok()
+        .and_then(|mut shard| shard.slots.remove(&graffiti))
+        .unwrap_or_default()
+        .into_iter()
+        .map(JsValue::from)
+        .collect::<Array>()
+        .unchecked_into()
+}
@@ -12,18 +12,27 @@ pub mod block_requests;
 pub mod blocks;
 pub mod deposits;
 pub mod epochs;
+pub mod execution_payloads;
 mod fetcher;
 pub mod good_peers;
+pub mod graffiti;
 mod page;
+pub mod search;
+pub mod slashings;
 pub mod sort;
+pub mod sync_committees;
 pub mod validators;
 pub mod views;
+pub mod voluntary_exits;
 
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(typescript_type = "string[]")]
     pub type StringArray;
 
+    #[wasm_bindgen(typescript_type = "bigint[]")]
+    pub type BigIntArray;
+
     #[wasm_bindgen(typescript_type = "[bigint | string, string][]")]
     pub type PathArray;
 
@@ -38,6 +47,33 @@ extern "C" {
 
     #[wasm_bindgen(typescript_type = "ValidatorView[]")]
     pub type ValidatorArray;
+
+    #[wasm_bindgen(typescript_type = "AttestationPerformanceView[]")]
+    pub type AttestationPerformanceArray;
+
+    #[wasm_bindgen(typescript_type = "ValidatorBalanceView[]")]
+    pub type ValidatorBalanceArray;
+
+    #[wasm_bindgen(typescript_type = "WithdrawalView[]")]
+    pub type WithdrawalArray;
+
+    #[wasm_bindgen(typescript_type = "BlsToExecutionChangeView[]")]
+    pub type BlsToExecutionChangeArray;
+
+    #[wasm_bindgen(typescript_type = "VoluntaryExitView[]")]
+    pub type VoluntaryExitArray;
+
+    #[wasm_bindgen(typescript_type = "ProposerSlashingView[]")]
+    pub type ProposerSlashingArray;
+
+    #[wasm_bindgen(typescript_type = "AttesterSlashingView[]")]
+    pub type AttesterSlashingArray;
+
+    #[wasm_bindgen(typescript_type = "SearchResultView[]")]
+    pub type SearchResultArray;
+
+    #[wasm_bindgen(typescript_type = "GraffitiTallyView[]")]
+    pub type GraffitiTallyArray;
 }
 
 #[wasm_bindgen(js_name = "getMeta")]
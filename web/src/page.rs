@@ -5,7 +5,7 @@ use futures::future::try_join_all;
 use js_sys::Array;
 use serde::Deserialize;
 use tsify::Tsify;
-use types::{path::ToPath, DeserializeOwned};
+use types::{meta::Meta, path::ToPath, DeserializeOwned};
 use wasm_bindgen::{prelude::wasm_bindgen, JsCast, JsValue};
 
 use crate::{
@@ -165,6 +165,7 @@ pub fn get_epoch_range(epoch: u64) -> Vec<ModelId> {
     range.map(|id| ModelId::AsU64(id as u64)).collect()
 }
 
+/// Fetches the chunks of the sorted index covering the page, then slices the page rows out of them
 async fn fetch_range<Id>(
     app: &App,
     input: RangeInput,
@@ -174,20 +175,30 @@ where
     Id: DeserializeOwned + Into<ModelId>,
 {
     let sort_by = SortBy::new(input.settings.sort_id, input.settings.sort_desc);
+    let field = sort_by.id.to_case(Case::Snake);
+
+    let page_number = input.settings.page_index + 1;
+    let page_size = input.settings.page_size;
+
+    // Without a meta, the index holds every model in chunks of the default size
+    let paginate =
+        match fetch::<Meta>(format!("{}/{}/meta.msg", app.base_url(), input.plural)).await {
+            Ok(meta) => Paginate::from_meta(&meta, &field, page_number, page_size, &sort_by),
+            Err(_) => Paginate::new(total_count, page_number, page_size, &sort_by),
+        };
+    let rows = paginate.rows();
+    let chunk_size = paginate.chunk_size();
+    let chunks = paginate.into_iter();
+    let offset = rows.start.saturating_sub((chunks.start - 1) * chunk_size);
 
     let mut futures = vec![];
-    for page_number in Paginate::new(
-        total_count,
-        input.settings.page_index + 1,
-        input.settings.page_size,
-        &sort_by,
-    ) {
+    for chunk_number in chunks {
         let url = format!(
             "{}/{}/s/{}/{}.msg",
             app.base_url(),
             input.plural,
-            sort_by.clone().id.to_case(Case::Snake),
-            page_number
+            field,
+            chunk_number
         );
         futures.push(fetch::<Vec<Id>>(url));
     }
@@ -196,6 +207,8 @@ where
         .await?
         .into_iter()
         .flatten()
+        .skip(offset)
+        .take(rows.len())
         .map(|id| id.into())
         .collect::<Vec<_>>();
 
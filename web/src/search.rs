@@ -0,0 +1,106 @@
+use js_sys::Array;
+use types::block::BlockModel;
+use types::epoch::EpochModel;
+use types::meta::Meta;
+use types::path::{Prefix, ToPath};
+use types::search::{hex_key, shard_id, text_keys, SearchResult, SearchShardModel};
+use types::validator::ValidatorModel;
+use types::validator_lookup::{self, PubkeyShardModel};
+use wasm_bindgen::prelude::*;
+
+use crate::app::App;
+use crate::fetcher::fetch;
+use crate::views::search::SearchResultView;
+use crate::{to_js, SearchResultArray};
+
+/// The most results a query resolves to
+const MAX_RESULTS: usize = 20;
+
+/// The length of a `0x` prefixed validator public key
+const PUBKEY_LEN: usize = 98;
+
+/// Resolves a slot, an epoch, a validator index, a block or state root, a graffiti word,
+/// a validator public key or the public key or withdrawal credentials of a deposit to the pages
+/// they belong to. Only the shards the query falls in are fetched.
+#[wasm_bindgen(js_name = "search")]
+pub async fn search(app: &App, query: String) -> Result<SearchResultArray, JsValue> {
+    let query = query.trim();
+
+    let results = match query.parse::<u64>() {
+        Ok(number) => search_number(app, number).await,
+        Err(_) => search_key(app, query).await,
+    };
+
+    results
+        .into_iter()
+        .map(SearchResultView::from)
+        .map(|v| to_js(&v))
+        .collect::<Result<Array, _>>()
+        .map(|a| a.unchecked_into())
+        .map_err(Into::into)
+}
+
+/// A number is a slot, an epoch or a validator index, as long as it has been indexed
+async fn search_number(app: &App, number: u64) -> Vec<SearchResult> {
+    let (blocks, epochs, validators) = futures::join!(
+        count::<BlockModel>(app),
+        count::<EpochModel>(app),
+        count::<ValidatorModel>(app)
+    );
+
+    vec![
+        (blocks, SearchResult::Block(number)),
+        (epochs, SearchResult::Epoch(number)),
+        (validators, SearchResult::Validator(number)),
+    ]
+    .into_iter()
+    .filter(|(count, _)| number < *count as u64)
+    .map(|(_, result)| result)
+    .collect()
+}
+
+async fn search_key(app: &App, query: &str) -> Vec<SearchResult> {
+    let is_hex = query
+        .strip_prefix("0x")
+        .filter(|hex| !hex.is_empty())
+        .is_some_and(|hex| hex.chars().all(|c| c.is_ascii_hexdigit()));
+
+    let key = match is_hex {
+        true => hex_key(query),
+        // A text query is looked up by its first word
+        false => match text_keys(query).into_iter().next() {
+            Some(key) => key,
+            None => return vec![],
+        },
+    };
+
+    let mut results = vec![];
+
+    if key.len() == PUBKEY_LEN {
+        let path = PubkeyShardModel::to_path(&app.base_url(), &validator_lookup::shard_id(&key));
+
+        if let Some(index) = fetch::<PubkeyShardModel>(path)
+            .await
+            .ok()
+            .and_then(|shard| shard.validators.get(&key).copied())
+        {
+            results.push(SearchResult::Validator(index));
+        }
+    }
+
+    // A shard without any entry isn't written
+    if let Ok(shard) =
+        fetch::<SearchShardModel>(SearchShardModel::to_path(&app.base_url(), &shard_id(&key))).await
+    {
+        results.extend(shard.find(&key, MAX_RESULTS - results.len()));
+    }
+
+    results
+}
+
+async fn count<M: Prefix>(app: &App) -> usize {
+    fetch::<Meta>(Meta::to_path::<M>(&app.base_url()))
+        .await
+        .map(|meta| meta.count)
+        .unwrap_or_default()
+}
@@ -0,0 +1,61 @@
+use js_sys::{Array, ArrayBuffer, JsString};
+use types::meta::Meta;
+use types::path::ToPath;
+use types::slashing::{AttesterSlashingModel, ProposerSlashingModel, SlashingModel};
+use wasm_bindgen::prelude::*;
+
+use crate::app::App;
+use crate::page::{get_paths, RangeInput};
+use crate::views::slashings::{AttesterSlashingView, ProposerSlashingView, SlashingView};
+use crate::{deserialize, to_js, AttesterSlashingArray, PathArray, ProposerSlashingArray};
+
+#[wasm_bindgen(js_name = "getSlashing")]
+pub fn get_slashing(buffer: ArrayBuffer, index: u64) -> Result<SlashingView, JsValue> {
+    let model = deserialize::<SlashingModel>(buffer)?;
+    Ok(SlashingView::from((index, model)))
+}
+
+#[wasm_bindgen(js_name = "getSlashingPath")]
+pub fn get_slashing_path(app: &App, index: u64) -> JsString {
+    SlashingModel::to_path(&app.base_url(), &index).into()
+}
+
+#[wasm_bindgen(js_name = "getSlashingRangePaths")]
+pub async fn get_slashing_range_paths(
+    app: &App,
+    input: RangeInput,
+    total_count: usize,
+) -> Result<PathArray, JsValue> {
+    get_paths::<SlashingModel>(app, input, total_count).await
+}
+
+#[wasm_bindgen(js_name = "getSlashingMetaPath")]
+pub fn get_slashing_meta_path(app: &App) -> JsString {
+    Meta::to_path::<SlashingModel>(&app.base_url()).into()
+}
+
+#[wasm_bindgen(js_name = "getProposerSlashings")]
+pub async fn get_proposer_slashings(
+    proposer_slashings_buffer: ArrayBuffer,
+) -> Result<ProposerSlashingArray, JsValue> {
+    deserialize::<Vec<ProposerSlashingModel>>(proposer_slashings_buffer)?
+        .into_iter()
+        .map(ProposerSlashingView::from)
+        .map(|v| to_js(&v))
+        .collect::<Result<Array, _>>()
+        .map(|a| a.unchecked_into())
+        .map_err(Into::into)
+}
+
+#[wasm_bindgen(js_name = "getAttesterSlashings")]
+pub async fn get_attester_slashings(
+    attester_slashings_buffer: ArrayBuffer,
+) -> Result<AttesterSlashingArray, JsValue> {
+    deserialize::<Vec<AttesterSlashingModel>>(attester_slashings_buffer)?
+        .into_iter()
+        .map(AttesterSlashingView::from)
+        .map(|v| to_js(&v))
+        .collect::<Result<Array, _>>()
+        .map(|a| a.unchecked_into())
+        .map_err(Into::into)
+}
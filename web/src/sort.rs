@@ -3,6 +3,8 @@ use std::{
     ops::Range,
 };
 
+use types::meta::{Meta, DEFAULT_SORTABLE_CHUNK_SIZE};
+
 #[derive(Clone)]
 pub struct SortBy {
     pub id: String,
@@ -19,6 +21,7 @@ pub struct Paginate<'a> {
     total_count: usize,
     page_number: usize,
     page_size: usize,
+    chunk_size: usize,
     sort_by: &'a SortBy,
 }
 
@@ -33,41 +36,69 @@ impl<'a> Paginate<'a> {
             total_count,
             page_number,
             page_size,
+            chunk_size: DEFAULT_SORTABLE_CHUNK_SIZE,
             sort_by,
         }
     }
+
+    /// Paginates the sorted index of `field`, which holds fewer ids than the models count when
+    /// the models before a checkpoint haven't been indexed
+    pub fn from_meta(
+        meta: &Meta,
+        field: &str,
+        page_number: usize,
+        page_size: usize,
+        sort_by: &'a SortBy,
+    ) -> Self {
+        let total_count = meta.sortable_length(field).unwrap_or(meta.count);
+
+        Paginate::new(total_count, page_number, page_size, sort_by)
+            .with_chunk_size(meta.sortable_chunk_size(field))
+    }
+
+    /// Sets how many ids the chunks of the sorted index hold
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = max(chunk_size, 1);
+        self
+    }
+
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// The positions of the page rows in the ascending sorted index
+    pub fn rows(&self) -> Range<usize> {
+        let start = (self.page_number - 1) * self.page_size;
+        let end = start + self.page_size;
+
+        match self.sort_by.desc {
+            true => self.total_count.saturating_sub(end)..self.total_count.saturating_sub(start),
+            false => min(start, self.total_count)..min(end, self.total_count),
+        }
+    }
 }
 
+/// The numbers of the chunks holding the page rows, starting at 1
 impl<'a> IntoIterator for Paginate<'a> {
     type Item = usize;
 
     type IntoIter = Range<usize>;
 
     fn into_iter(self) -> Self::IntoIter {
-        let page_count = self.page_size / 10;
-        let total_page_count =
-            self.total_count / 10 + if self.total_count % 10 != 0 { 1 } else { 0 };
-
-        let range = match self.sort_by.desc {
-            true => {
-                let mut start = total_page_count + 1 - (self.page_number) * page_count;
-                let end = total_page_count + 1 - (self.page_number - 1) * page_count;
-
-                if self.total_count % 10 != 0 {
-                    start -= 1;
-                }
-
-                start..end
-            }
-            false => 1 + (self.page_number - 1) * page_count..self.page_number * page_count + 1,
-        };
+        let rows = self.rows();
 
-        max(range.start, 1)..min(range.end, total_page_count + 1)
+        if rows.is_empty() {
+            return 1..1;
+        }
+
+        rows.start / self.chunk_size + 1..(rows.end - 1) / self.chunk_size + 2
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use types::meta::Meta;
+
     use crate::sort::{Paginate, SortBy};
 
     #[test]
@@ -135,4 +166,67 @@ mod tests {
         assert_eq!(page2.into_iter(), 3354..3358);
         assert_eq!(page3.into_iter(), 3351..3355);
     }
+
+    #[test]
+    fn asc_larger_chunks() {
+        let sort_by = SortBy::new("attestations_count".to_string(), false);
+        let page1 = Paginate::new(33596, 1, 30, &sort_by).with_chunk_size(100);
+        let page4 = Paginate::new(33596, 4, 30, &sort_by).with_chunk_size(100);
+        assert_eq!(page1.rows(), 0..30);
+        assert_eq!(page1.into_iter(), 1..2);
+        assert_eq!(page4.rows(), 90..120);
+        assert_eq!(page4.into_iter(), 1..3);
+    }
+
+    #[test]
+    fn desc_page_size_not_multiple_of_chunk_size() {
+        let sort_by = SortBy::new("attestations_count".to_string(), true);
+        let page1 = Paginate::new(33596, 1, 25, &sort_by);
+        let page2 = Paginate::new(33596, 2, 25, &sort_by);
+        assert_eq!(page1.rows(), 33571..33596);
+        assert_eq!(page1.into_iter(), 3358..3361);
+        assert_eq!(page2.rows(), 33546..33571);
+        assert_eq!(page2.into_iter(), 3355..3359);
+    }
+
+    #[test]
+    fn last_partial_page() {
+        let sort_by = SortBy::new("attestations_count".to_string(), false);
+        let last = Paginate::new(33596, 1344, 25, &sort_by);
+        let past_end = Paginate::new(33596, 1345, 25, &sort_by);
+        assert_eq!(last.rows(), 33575..33596);
+        assert_eq!(last.into_iter(), 3358..3361);
+        assert_eq!(past_end.into_iter(), 1..1);
+    }
+
+    #[test]
+    fn desc_partial_index() {
+        let sort_by = SortBy::new("attestations_count".to_string(), true);
+
+        // Indexed from a checkpoint at slot 900, the sorted index only holds the slots after it
+        let mut meta = Meta::partial(900, 0..900);
+        (900..1000).for_each(|slot| meta.mark_indexed(slot));
+        meta.sortable_lengths
+            .insert("attestations_count".to_string(), 100);
+
+        let page1 = Paginate::from_meta(&meta, "attestations_count", 1, 10, &sort_by);
+        let page10 = Paginate::from_meta(&meta, "attestations_count", 10, 10, &sort_by);
+        let past_end = Paginate::from_meta(&meta, "attestations_count", 11, 10, &sort_by);
+        assert_eq!(page1.rows(), 90..100);
+        assert_eq!(page1.into_iter(), 10..11);
+        assert_eq!(page10.into_iter(), 1..2);
+        assert_eq!(past_end.into_iter(), 1..1);
+    }
+
+    #[test]
+    fn index_without_recorded_length() {
+        let sort_by = SortBy::new("attestations_count".to_string(), true);
+        let meta = Meta {
+            count: 33600,
+            ..Default::default()
+        };
+
+        let page1 = Paginate::from_meta(&meta, "attestations_count", 1, 10, &sort_by);
+        assert_eq!(page1.into_iter(), 3360..3361);
+    }
 }
@@ -0,0 +1,25 @@
+use js_sys::{ArrayBuffer, JsString};
+use types::path::ToPath;
+use types::sync_committee::{SyncAggregateModel, SyncCommitteeModel};
+use wasm_bindgen::prelude::*;
+
+use crate::app::App;
+use crate::deserialize;
+use crate::views::sync_committees::{SyncAggregateView, SyncCommitteeView};
+
+#[wasm_bindgen(js_name = "getSyncCommittee")]
+pub fn get_sync_committee(buffer: ArrayBuffer, period: u64) -> Result<SyncCommitteeView, JsValue> {
+    let model = deserialize::<SyncCommitteeModel>(buffer)?;
+    Ok(SyncCommitteeView::from((period, model)))
+}
+
+#[wasm_bindgen(js_name = "getSyncCommitteePath")]
+pub fn get_sync_committee_path(app: &App, period: u64) -> JsString {
+    SyncCommitteeModel::to_path(&app.base_url(), &period).into()
+}
+
+#[wasm_bindgen(js_name = "getSyncAggregate")]
+pub fn get_sync_aggregate(buffer: ArrayBuffer, slot: u64) -> Result<SyncAggregateView, JsValue> {
+    let model = deserialize::<SyncAggregateModel>(buffer)?;
+    Ok(SyncAggregateView::from((slot, model)))
+}
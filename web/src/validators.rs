@@ -1,13 +1,22 @@
-use js_sys::{ArrayBuffer, JsString};
+use futures::future::join_all;
+use js_sys::{Array, ArrayBuffer, JsString};
+use types::attestation_performance::AttestationPerformanceModel;
 use types::meta::Meta;
 use types::path::ToPath;
 use types::validator::{ValidatorExtendedModel, ValidatorModel};
+use types::validator_balances::ValidatorBalancesModel;
+use types::validator_lookup::{normalize, shard_id, PubkeyShardModel, WithdrawalAddressShardModel};
 use wasm_bindgen::prelude::*;
 
 use crate::app::App;
+use crate::fetcher::fetch;
 use crate::page::{get_paths, RangeInput};
-use crate::views::validators::{ValidatorPaths, ValidatorView};
-use crate::{deserialize, PathArray};
+use crate::views::attestation_performances::AttestationPerformanceView;
+use crate::views::validator_balances::ValidatorBalanceView;
+use crate::views::validators::{ValidatorExtendedView, ValidatorPaths, ValidatorView};
+use crate::{
+    deserialize, to_js, AttestationPerformanceArray, BigIntArray, PathArray, ValidatorBalanceArray,
+};
 
 #[wasm_bindgen(js_name = "getValidator")]
 pub fn get_validator(buffer: ArrayBuffer, index: u64) -> Result<ValidatorView, JsValue> {
@@ -15,6 +24,17 @@ pub fn get_validator(buffer: ArrayBuffer, index: u64) -> Result<ValidatorView, J
     Ok(ValidatorView::from((index, model)))
 }
 
+#[wasm_bindgen(js_name = "getValidatorExtended")]
+pub fn get_validator_extended(
+    model_buffer: ArrayBuffer,
+    extended_model_buffer: ArrayBuffer,
+    index: u64,
+) -> Result<ValidatorExtendedView, JsValue> {
+    let model = deserialize::<ValidatorModel>(model_buffer)?;
+    let extended_model = deserialize::<ValidatorExtendedModel>(extended_model_buffer)?;
+    Ok(ValidatorExtendedView::from((index, model, extended_model)))
+}
+
 #[wasm_bindgen(js_name = "getValidatorPaths")]
 pub fn get_validator_paths(app: &App, index: u64) -> ValidatorPaths {
     ValidatorPaths {
@@ -23,6 +43,95 @@ pub fn get_validator_paths(app: &App, index: u64) -> ValidatorPaths {
     }
 }
 
+/// The attestation duties of a validator in the epochs `start_epoch..end_epoch`.
+/// Chunks which haven't been indexed are skipped.
+#[wasm_bindgen(js_name = "getAttestationPerformances")]
+pub async fn get_attestation_performances(
+    app: &App,
+    index: u64,
+    start_epoch: u64,
+    end_epoch: u64,
+) -> Result<AttestationPerformanceArray, JsValue> {
+    let chunks = AttestationPerformanceModel::chunk_ids(index, start_epoch, end_epoch)
+        .into_iter()
+        .map(|id| {
+            fetch::<Vec<AttestationPerformanceModel>>(AttestationPerformanceModel::to_path(
+                &app.base_url(),
+                &id,
+            ))
+        });
+
+    join_all(chunks)
+        .await
+        .into_iter()
+        .filter_map(Result::ok)
+        .flatten()
+        .filter(|duty| (start_epoch..end_epoch).contains(&duty.epoch))
+        .map(AttestationPerformanceView::from)
+        .map(|v| to_js(&v))
+        .collect::<Result<Array, _>>()
+        .map(|a| a.unchecked_into())
+        .map_err(Into::into)
+}
+
+/// The balances of a validator at the end of the epochs `start_epoch..end_epoch`.
+/// Chunks which haven't been indexed are skipped.
+#[wasm_bindgen(js_name = "getValidatorBalances")]
+pub async fn get_validator_balances(
+    app: &App,
+    index: u64,
+    start_epoch: u64,
+    end_epoch: u64,
+) -> Result<ValidatorBalanceArray, JsValue> {
+    let chunks = ValidatorBalancesModel::chunk_ids(index, start_epoch, end_epoch)
+        .into_iter()
+        .map(|id| {
+            fetch::<ValidatorBalancesModel>(ValidatorBalancesModel::to_path(&app.base_url(), &id))
+        });
+
+    join_all(chunks)
+        .await
+        .into_iter()
+        .filter_map(Result::ok)
+        .flat_map(|chunk| chunk.series().collect::<Vec<_>>())
+        .filter(|(epoch, _, _)| (start_epoch..end_epoch).contains(epoch))
+        .map(ValidatorBalanceView::from)
+        .map(|v| to_js(&v))
+        .collect::<Result<Array, _>>()
+        .map(|a| a.unchecked_into())
+        .map_err(Into::into)
+}
+
+/// The index of the validator with this public key, fetching only the shard it belongs to
+#[wasm_bindgen(js_name = "findValidatorByPubkey")]
+pub async fn find_validator_by_pubkey(app: &App, pubkey: String) -> Option<u64> {
+    let pubkey = normalize(&pubkey);
+    let path = PubkeyShardModel::to_path(&app.base_url(), &shard_id(&pubkey));
+
+    // A shard without any validator isn't written
+    fetch::<PubkeyShardModel>(path)
+        .await
+        .ok()
+        .and_then(|shard| shard.validators.get(&pubkey).copied())
+}
+
+/// The indices of the validators withdrawing to this execution address, fetching only its shard
+#[wasm_bindgen(js_name = "findValidatorsByWithdrawalAddress")]
+pub async fn find_validators_by_withdrawal_address(app: &App, address: String) -> BigIntArray {
+    let address = normalize(&address);
+    let path = WithdrawalAddressShardModel::to_path(&app.base_url(), &shard_id(&address));
+
+    fetch::<WithdrawalAddressShardModel>(path)
+        .await
+        .ok()
+        .and_then(|mut shard| shard.validators.remove(&address))
+        .unwrap_or_default()
+        .into_iter()
+        .map(JsValue::from)
+        .collect::<Array>()
+        .unchecked_into()
+}
+
 #[wasm_bindgen(js_name = "getValidatorRangePaths")]
 pub async fn get_validator_range_paths(
     app: &App,
@@ -0,0 +1,17 @@
+use serde::Serialize;
+use tsify::Tsify;
+use types::attestation_performance::AttestationPerformanceModel;
+
+#[derive(Serialize, Tsify, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestationPerformanceView {
+    #[serde(flatten)]
+    pub model: AttestationPerformanceModel,
+}
+
+impl From<AttestationPerformanceModel> for AttestationPerformanceView {
+    fn from(model: AttestationPerformanceModel) -> Self {
+        AttestationPerformanceView { model }
+    }
+}
@@ -69,4 +69,11 @@ pub struct BlockPaths {
     pub committees: String,
     pub votes: String,
     pub attestations: String,
+    pub sync_aggregate: String,
+    pub execution_payload: String,
+    pub withdrawals: String,
+    pub bls_to_execution_changes: String,
+    pub voluntary_exits: String,
+    pub proposer_slashings: String,
+    pub attester_slashings: String,
 }
@@ -76,4 +76,5 @@ impl From<EpochExtendedView> for JsValue {
 pub struct EpochPaths {
     pub epoch: String,
     pub epoch_extended: String,
+    pub graffiti_tally: String,
 }
@@ -0,0 +1,26 @@
+use serde::Serialize;
+use tsify::Tsify;
+use types::execution_payload::ExecutionPayloadModel;
+use wasm_bindgen::JsValue;
+
+use crate::to_js;
+
+#[derive(Serialize, Tsify, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+pub struct ExecutionPayloadView {
+    pub slot: u64,
+    #[serde(flatten)]
+    pub model: ExecutionPayloadModel,
+}
+
+impl From<(u64, ExecutionPayloadModel)> for ExecutionPayloadView {
+    fn from((slot, model): (u64, ExecutionPayloadModel)) -> Self {
+        ExecutionPayloadView { slot, model }
+    }
+}
+
+impl From<ExecutionPayloadView> for JsValue {
+    fn from(val: ExecutionPayloadView) -> Self {
+        to_js(&val).unwrap()
+    }
+}
@@ -0,0 +1,18 @@
+use serde::Serialize;
+use tsify::Tsify;
+use types::graffiti::GraffitiTallyModel;
+
+#[derive(Serialize, Tsify, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct GraffitiTallyView {
+    pub epoch: u64,
+    #[serde(flatten)]
+    pub model: GraffitiTallyModel,
+}
+
+impl From<(u64, GraffitiTallyModel)> for GraffitiTallyView {
+    fn from((epoch, model): (u64, GraffitiTallyModel)) -> Self {
+        GraffitiTallyView { epoch, model }
+    }
+}
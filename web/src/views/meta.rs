@@ -9,13 +9,22 @@ use crate::to_js;
 
 #[derive(Serialize, Tsify, Debug, Clone)]
 #[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
 pub struct MetaView {
     pub count: usize,
+    pub not_indexed_start: Option<u64>,
+    pub not_indexed_end: Option<u64>,
 }
 
 impl From<Meta> for MetaView {
     fn from(meta: Meta) -> Self {
-        MetaView { count: meta.count }
+        let not_indexed = meta.not_indexed();
+
+        MetaView {
+            count: meta.count,
+            not_indexed_start: not_indexed.as_ref().map(|r| r.start),
+            not_indexed_end: not_indexed.map(|r| r.end),
+        }
     }
 }
 
@@ -38,11 +47,11 @@ impl TryFrom<Meta> for DepositMetaView {
 
     fn try_from(value: Meta) -> Result<Self, Self::Error> {
         match value.specific {
-            MetaSpecific::Empty => Err("Invalid meta type".to_string()),
             MetaSpecific::Deposit(meta) => Ok(DepositMetaView {
                 count: value.count,
                 meta,
             }),
+            _ => Err("Invalid meta type".to_string()),
         }
     }
 }
@@ -1,10 +1,19 @@
+pub mod attestation_performances;
 pub mod attestations;
 pub mod block_requests;
 pub mod blocks;
 pub mod committees;
 pub mod deposits;
 pub mod epochs;
+pub mod execution_payloads;
 pub mod good_peers;
+pub mod graffiti;
 pub mod meta;
+pub mod search;
+pub mod slashings;
+pub mod sync_committees;
+pub mod validator_balances;
 pub mod validators;
+pub mod voluntary_exits;
 pub mod votes;
+pub mod withdrawals;
@@ -0,0 +1,17 @@
+use serde::Serialize;
+use tsify::Tsify;
+use types::search::SearchResult;
+
+#[derive(Serialize, Tsify, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResultView {
+    #[serde(flatten)]
+    pub result: SearchResult,
+}
+
+impl From<SearchResult> for SearchResultView {
+    fn from(result: SearchResult) -> Self {
+        SearchResultView { result }
+    }
+}
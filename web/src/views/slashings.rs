@@ -0,0 +1,55 @@
+use serde::Serialize;
+use tsify::Tsify;
+use types::slashing::{AttesterSlashingModel, ProposerSlashingModel, SlashingModel};
+use wasm_bindgen::JsValue;
+
+use crate::to_js;
+
+#[derive(Serialize, Tsify, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposerSlashingView {
+    #[serde(flatten)]
+    pub model: ProposerSlashingModel,
+}
+
+impl From<ProposerSlashingModel> for ProposerSlashingView {
+    fn from(model: ProposerSlashingModel) -> Self {
+        ProposerSlashingView { model }
+    }
+}
+
+#[derive(Serialize, Tsify, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct AttesterSlashingView {
+    #[serde(flatten)]
+    pub model: AttesterSlashingModel,
+}
+
+impl From<AttesterSlashingModel> for AttesterSlashingView {
+    fn from(model: AttesterSlashingModel) -> Self {
+        AttesterSlashingView { model }
+    }
+}
+
+#[derive(Serialize, Tsify, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct SlashingView {
+    pub index: u64,
+    #[serde(flatten)]
+    pub model: SlashingModel,
+}
+
+impl From<(u64, SlashingModel)> for SlashingView {
+    fn from((index, model): (u64, SlashingModel)) -> Self {
+        SlashingView { index, model }
+    }
+}
+
+impl From<SlashingView> for JsValue {
+    fn from(val: SlashingView) -> Self {
+        to_js(&val).unwrap()
+    }
+}
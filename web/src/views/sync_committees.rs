@@ -0,0 +1,46 @@
+use serde::Serialize;
+use tsify::Tsify;
+use types::sync_committee::{SyncAggregateModel, SyncCommitteeModel};
+use wasm_bindgen::JsValue;
+
+use crate::to_js;
+
+#[derive(Serialize, Tsify, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+pub struct SyncCommitteeView {
+    pub period: u64,
+    #[serde(flatten)]
+    pub model: SyncCommitteeModel,
+}
+
+impl From<(u64, SyncCommitteeModel)> for SyncCommitteeView {
+    fn from((period, model): (u64, SyncCommitteeModel)) -> Self {
+        SyncCommitteeView { period, model }
+    }
+}
+
+impl From<SyncCommitteeView> for JsValue {
+    fn from(val: SyncCommitteeView) -> Self {
+        to_js(&val).unwrap()
+    }
+}
+
+#[derive(Serialize, Tsify, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+pub struct SyncAggregateView {
+    pub slot: u64,
+    #[serde(flatten)]
+    pub model: SyncAggregateModel,
+}
+
+impl From<(u64, SyncAggregateModel)> for SyncAggregateView {
+    fn from((slot, model): (u64, SyncAggregateModel)) -> Self {
+        SyncAggregateView { slot, model }
+    }
+}
+
+impl From<SyncAggregateView> for JsValue {
+    fn from(val: SyncAggregateView) -> Self {
+        to_js(&val).unwrap()
+    }
+}
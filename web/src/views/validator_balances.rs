@@ -0,0 +1,21 @@
+use serde::Serialize;
+use tsify::Tsify;
+
+#[derive(Serialize, Tsify, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorBalanceView {
+    pub epoch: u64,
+    pub balance: u64,
+    pub effective_balance: u64,
+}
+
+impl From<(u64, u64, u64)> for ValidatorBalanceView {
+    fn from((epoch, balance, effective_balance): (u64, u64, u64)) -> Self {
+        ValidatorBalanceView {
+            epoch,
+            balance,
+            effective_balance,
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use serde::Serialize;
 use tsify::Tsify;
-use types::validator::ValidatorModel;
+use types::validator::{ValidatorExtendedModel, ValidatorModel};
 use wasm_bindgen::JsValue;
 
 use crate::to_js;
@@ -22,12 +22,38 @@ impl From<(u64, ValidatorModel)> for ValidatorView {
     }
 }
 
+#[derive(Serialize, Tsify, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+pub struct ValidatorExtendedView {
+    pub validator_index: u64,
+    #[serde(flatten)]
+    pub model: ValidatorModel,
+    #[serde(flatten)]
+    pub extended_model: ValidatorExtendedModel,
+}
+
+impl From<(u64, ValidatorModel, ValidatorExtendedModel)> for ValidatorExtendedView {
+    fn from((id, model, extended_model): (u64, ValidatorModel, ValidatorExtendedModel)) -> Self {
+        ValidatorExtendedView {
+            validator_index: id,
+            model,
+            extended_model,
+        }
+    }
+}
+
 impl From<ValidatorView> for JsValue {
     fn from(val: ValidatorView) -> Self {
         to_js(&val).unwrap()
     }
 }
 
+impl From<ValidatorExtendedView> for JsValue {
+    fn from(val: ValidatorExtendedView) -> Self {
+        to_js(&val).unwrap()
+    }
+}
+
 #[derive(Serialize, Tsify, Debug, Clone)]
 #[tsify(into_wasm_abi)]
 #[serde(rename_all = "camelCase")]
@@ -0,0 +1,41 @@
+use serde::Serialize;
+use tsify::Tsify;
+use types::voluntary_exit::{IndexedVoluntaryExitModel, VoluntaryExitModel};
+use wasm_bindgen::JsValue;
+
+use crate::to_js;
+
+#[derive(Serialize, Tsify, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct VoluntaryExitView {
+    #[serde(flatten)]
+    pub model: VoluntaryExitModel,
+}
+
+impl From<VoluntaryExitModel> for VoluntaryExitView {
+    fn from(model: VoluntaryExitModel) -> Self {
+        VoluntaryExitView { model }
+    }
+}
+
+#[derive(Serialize, Tsify, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedVoluntaryExitView {
+    pub index: u64,
+    #[serde(flatten)]
+    pub model: IndexedVoluntaryExitModel,
+}
+
+impl From<(u64, IndexedVoluntaryExitModel)> for IndexedVoluntaryExitView {
+    fn from((index, model): (u64, IndexedVoluntaryExitModel)) -> Self {
+        IndexedVoluntaryExitView { index, model }
+    }
+}
+
+impl From<IndexedVoluntaryExitView> for JsValue {
+    fn from(val: IndexedVoluntaryExitView) -> Self {
+        to_js(&val).unwrap()
+    }
+}
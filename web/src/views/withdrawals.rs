@@ -0,0 +1,31 @@
+use serde::Serialize;
+use tsify::Tsify;
+use types::withdrawal::{BlsToExecutionChangeModel, WithdrawalModel};
+
+#[derive(Serialize, Tsify, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct WithdrawalView {
+    #[serde(flatten)]
+    pub model: WithdrawalModel,
+}
+
+impl From<WithdrawalModel> for WithdrawalView {
+    fn from(model: WithdrawalModel) -> Self {
+        WithdrawalView { model }
+    }
+}
+
+#[derive(Serialize, Tsify, Debug, Clone)]
+#[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct BlsToExecutionChangeView {
+    #[serde(flatten)]
+    pub model: BlsToExecutionChangeModel,
+}
+
+impl From<BlsToExecutionChangeModel> for BlsToExecutionChangeView {
+    fn from(model: BlsToExecutionChangeModel) -> Self {
+        BlsToExecutionChangeView { model }
+    }
+}
@@ -0,0 +1,45 @@
+use js_sys::{Array, ArrayBuffer, JsString};
+use types::meta::Meta;
+use types::voluntary_exit::{IndexedVoluntaryExitModel, VoluntaryExitModel};
+use wasm_bindgen::prelude::*;
+
+use crate::app::App;
+use crate::page::{get_paths, RangeInput};
+use crate::views::voluntary_exits::{IndexedVoluntaryExitView, VoluntaryExitView};
+use crate::{deserialize, to_js, PathArray, VoluntaryExitArray};
+
+#[wasm_bindgen(js_name = "getVoluntaryExit")]
+pub fn get_voluntary_exit(
+    buffer: ArrayBuffer,
+    index: u64,
+) -> Result<IndexedVoluntaryExitView, JsValue> {
+    let model = deserialize::<IndexedVoluntaryExitModel>(buffer)?;
+    Ok(IndexedVoluntaryExitView::from((index, model)))
+}
+
+#[wasm_bindgen(js_name = "getVoluntaryExitRangePaths")]
+pub async fn get_voluntary_exit_range_paths(
+    app: &App,
+    input: RangeInput,
+    total_count: usize,
+) -> Result<PathArray, JsValue> {
+    get_paths::<IndexedVoluntaryExitModel>(app, input, total_count).await
+}
+
+#[wasm_bindgen(js_name = "getVoluntaryExitMetaPath")]
+pub fn get_voluntary_exit_meta_path(app: &App) -> JsString {
+    Meta::to_path::<IndexedVoluntaryExitModel>(&app.base_url()).into()
+}
+
+#[wasm_bindgen(js_name = "getVoluntaryExits")]
+pub async fn get_voluntary_exits(
+    voluntary_exits_buffer: ArrayBuffer,
+) -> Result<VoluntaryExitArray, JsValue> {
+    deserialize::<Vec<VoluntaryExitModel>>(voluntary_exits_buffer)?
+        .into_iter()
+        .map(VoluntaryExitView::from)
+        .map(|v| to_js(&v))
+        .collect::<Result<Array, _>>()
+        .map(|a| a.unchecked_into())
+        .map_err(Into::into)
+}